@@ -0,0 +1,276 @@
+//! Decodes the calls, i.e., the pallet index, call index and arguments, e.g., the calls stored in
+//! `Scheduler::Agenda` and `Democracy::Preimages`.
+//!
+//! Like the events, the outer `Call` enum of the runtime is indexed by the position of the pallet
+//! among the pallets with calls. [`register_call_types`] registers it in the [`ValueDecoder`] so
+//! the storage values containing `<T as Trait>::Call` are decoded as well.
+//!
+//! [`encode_call`] does the reverse, encoding the call from the JSON arguments, e.g., to craft
+//! the `Sudo::sudo` and governance call payloads from scripts.
+
+use std::fmt;
+
+use codec::Decode;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::decoder::{DecodeError, TypeDef, ValueDecoder};
+use crate::encoder::{EncodeError, ValueEncoder};
+use crate::metadata::{Encoded, Metadata, MetadataError};
+use crate::value::Value;
+
+/// Error of decoding a call.
+#[derive(Debug, thiserror::Error)]
+pub enum CallError {
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    #[error(transparent)]
+    Encode(#[from] EncodeError),
+}
+
+/// Decoded call with the named arguments.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DecodedCall {
+    pub pallet: String,
+    pub call: String,
+    /// `Value::Composite` of the arguments by name.
+    pub args: Value,
+}
+
+impl fmt::Display for DecodedCall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}::{}{}", self.pallet, self.call, self.args)
+    }
+}
+
+/// Name of the type of the calls of `pallet`.
+fn pallet_call_type(pallet: &str) -> String {
+    format!("{}Call", pallet)
+}
+
+/// Registers the `Call` enum of the runtime, plus the call enum of each pallet, in `decoder`.
+pub fn register_call_types(decoder: &mut ValueDecoder, metadata: &Metadata) {
+    let mut modules = metadata.modules_with_calls.values().collect::<Vec<_>>();
+    modules.sort_by_key(|module| module.index());
+
+    let mut pallets = Vec::with_capacity(modules.len());
+    for module in modules {
+        let calls = module
+            .calls()
+            .map(|call| {
+                let args = call.arguments().iter().map(|arg| arg.ty.clone()).collect();
+                (call.name.clone(), args)
+            })
+            .collect();
+        let call_ty = pallet_call_type(module.name());
+        decoder.register(call_ty.clone(), TypeDef::Variant(calls));
+        pallets.push((module.name().to_string(), vec![call_ty]));
+    }
+    decoder.register("Call", TypeDef::Variant(pallets));
+}
+
+/// Decodes the call at the start of `input`.
+pub fn decode_call(
+    metadata: &Metadata,
+    decoder: &ValueDecoder,
+    input: &mut &[u8],
+) -> Result<DecodedCall, CallError> {
+    let module =
+        metadata.module_with_calls_by_index(u8::decode(input).map_err(DecodeError::from)?)?;
+    let call = module.call_by_index(u8::decode(input).map_err(DecodeError::from)?)?;
+    let args = call
+        .arguments()
+        .iter()
+        .map(|arg| Ok((arg.name.clone(), decoder.decode(&arg.ty, input)?)))
+        .collect::<Result<_, DecodeError>>()?;
+    Ok(DecodedCall {
+        pallet: module.name().into(),
+        call: call.name.clone(),
+        args: Value::Composite(args),
+    })
+}
+
+/// Decodes the call `encoded`, which must be consumed completely.
+pub fn decode_call_all(
+    metadata: &Metadata,
+    decoder: &ValueDecoder,
+    encoded: &[u8],
+) -> Result<DecodedCall, CallError> {
+    let mut input = encoded;
+    let call = decode_call(metadata, decoder, &mut input)?;
+    if !input.is_empty() {
+        return Err(DecodeError::TrailingBytes(input.len()).into());
+    }
+    Ok(call)
+}
+
+/// Encodes the call `pallet::call` with the JSON `args`, either an object of the arguments by
+/// name or an array of the arguments in order.
+///
+/// The arguments are in the same JSON as the `args` of [`DecodedCall`], e.g.,
+/// `{"dest": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "value": "1000"}` for
+/// `Balances::transfer`. The nested calls of `Sudo::sudo` and `Utility::batch` require
+/// [`register_call_types`].
+pub fn encode_call(
+    metadata: &Metadata,
+    decoder: &ValueDecoder,
+    pallet: &str,
+    call: &str,
+    args: &JsonValue,
+) -> Result<Encoded, CallError> {
+    let module = metadata.module_with_calls(pallet)?;
+    let call = module.call_by_name(call)?;
+    let arguments = call.arguments();
+    let encoder = ValueEncoder::new(decoder);
+
+    let mut encoded = vec![module.index(), call.index()];
+    match args {
+        JsonValue::Array(values) if values.len() == arguments.len() => {
+            for (arg, value) in arguments.iter().zip(values) {
+                encoder.encode_to(&arg.ty, value, &mut encoded)?;
+            }
+        }
+        JsonValue::Object(values) => {
+            for arg in arguments {
+                let value = values.get(&arg.name).ok_or_else(|| {
+                    EncodeError::MissingField(
+                        format!("{}::{}", module.name(), call.name),
+                        arg.name.clone(),
+                    )
+                })?;
+                encoder.encode_to(&arg.ty, value, &mut encoded)?;
+            }
+        }
+        JsonValue::Null if arguments.is_empty() => {}
+        _ => {
+            return Err(EncodeError::InvalidValue(
+                format!("{}::{}", module.name(), call.name),
+                args.clone(),
+            )
+            .into())
+        }
+    }
+    Ok(Encoded(encoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::test_metadata;
+    use codec::{Compact, Encode};
+    use serde_json::json;
+
+    fn call_index(metadata: &Metadata, pallet: &str, call: &str) -> (u8, u8) {
+        let module = metadata.module_with_calls(pallet).unwrap();
+        (module.index(), module.call_by_name(call).unwrap().index())
+    }
+
+    #[test]
+    fn decode_call_should_work() {
+        let metadata = test_metadata();
+        let decoder = ValueDecoder::default();
+
+        // Balances::transfer(dest: Id([1; 32]), value: 100)
+        let (pallet, call) = call_index(&metadata, "Balances", "transfer");
+        let transfer = (pallet, call, 0xffu8, [1u8; 32], Compact(100u128)).encode();
+        let decoded = decode_call_all(&metadata, &decoder, &transfer).unwrap();
+        assert_eq!(decoded.pallet, "Balances");
+        assert_eq!(decoded.call, "transfer");
+        assert_eq!(
+            decoded.args,
+            Value::composite(vec![
+                (
+                    "dest",
+                    Value::Variant("Id".into(), vec![Value::Bytes(vec![1; 32])])
+                ),
+                ("value", Value::U128(100)),
+            ])
+        );
+
+        // Utility::batch(calls: vec![transfer]) via the registered `Call` type.
+        let mut decoder = decoder;
+        register_call_types(&mut decoder, &metadata);
+        let (pallet, call) = call_index(&metadata, "Utility", "batch");
+        let mut batch = vec![pallet, call];
+        batch.extend(Compact(1u32).encode());
+        batch.extend(&transfer);
+        let decoded = decode_call_all(&metadata, &decoder, &batch).unwrap();
+        assert_eq!(
+            decoded.args,
+            Value::composite(vec![(
+                "calls",
+                Value::Sequence(vec![Value::Variant(
+                    "Balances".into(),
+                    vec![Value::Variant(
+                        "transfer".into(),
+                        vec![
+                            Value::Variant("Id".into(), vec![Value::Bytes(vec![1; 32])]),
+                            Value::U128(100)
+                        ]
+                    )]
+                )])
+            )])
+        );
+
+        assert!(matches!(
+            decode_call_all(&metadata, &decoder, &[255, 0]),
+            Err(CallError::Metadata(MetadataError::ModuleIndexNotFound(255)))
+        ));
+    }
+
+    #[test]
+    fn encode_call_should_work() {
+        let metadata = test_metadata();
+        let mut decoder = ValueDecoder::default();
+        register_call_types(&mut decoder, &metadata);
+
+        let (pallet, call) = call_index(&metadata, "Balances", "transfer");
+        let transfer = (pallet, call, 0xffu8, [1u8; 32], Compact(100u128)).encode();
+        let dest = format!("0x{}", hex::encode([1u8; 32]));
+        let by_name = json!({ "dest": dest, "value": "100" });
+        assert_eq!(
+            encode_call(&metadata, &decoder, "Balances", "transfer", &by_name)
+                .unwrap()
+                .0,
+            transfer
+        );
+        let in_order = json!([{ "Id": dest }, 100]);
+        assert_eq!(
+            encode_call(&metadata, &decoder, "Balances", "transfer", &in_order)
+                .unwrap()
+                .0,
+            transfer
+        );
+
+        // The decoded JSON of a nested call can be encoded again.
+        let (pallet, call) = call_index(&metadata, "Utility", "batch");
+        let mut batch = vec![pallet, call];
+        batch.extend(Compact(1u32).encode());
+        batch.extend(&transfer);
+        let decoded = decode_call_all(&metadata, &decoder, &batch).unwrap();
+        let args = serde_json::to_value(&decoded.args).unwrap();
+        assert_eq!(
+            encode_call(&metadata, &decoder, "Utility", "batch", &args)
+                .unwrap()
+                .0,
+            batch
+        );
+
+        assert!(matches!(
+            encode_call(
+                &metadata,
+                &decoder,
+                "Balances",
+                "transfer",
+                &json!({ "value": 1 })
+            ),
+            Err(CallError::Encode(EncodeError::MissingField(..)))
+        ));
+        assert!(matches!(
+            encode_call(&metadata, &decoder, "Balances", "unknown", &json!([])),
+            Err(CallError::Metadata(MetadataError::CallNotFound(_)))
+        ));
+    }
+}
@@ -0,0 +1,153 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::Deserialize;
+
+use crate::state_source::{ChildTriple, StoragePair};
+use crate::strip_hex_prefix;
+
+/// Storage key of the runtime code, i.e., `hex(":code")`.
+pub const CODE_KEY: &str = "3a636f6465";
+
+/// Chain spec error.
+#[derive(Debug, thiserror::Error)]
+pub enum ChainSpecError {
+    /// Failed to read the chain spec file.
+    #[error("Failed to read chain spec: {0}")]
+    Io(#[from] std::io::Error),
+    /// Failed to parse the chain spec.
+    #[error("Invalid chain spec: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The genesis is not in raw storage.
+    #[error("Chain spec is not raw, please convert it with `build-spec --raw`")]
+    NotRaw,
+    /// Storage key or value is not valid hex.
+    #[error("Invalid hex {0}: {1}")]
+    InvalidHex(String, hex::FromHexError),
+    /// The genesis storage has no `:code`.
+    #[error("`:code` not found in genesis storage")]
+    MissingCode,
+}
+
+/// Genesis storage of a raw chain spec.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawGenesis {
+    /// Top trie storage, `0x` prefixed hex key to `0x` prefixed hex value.
+    pub top: BTreeMap<String, String>,
+    /// Child trie storage, keyed by the child storage key.
+    #[serde(default)]
+    pub children_default: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Genesis {
+    Raw(RawGenesis),
+    /// The genesis config of the runtime, which is not decoded.
+    Runtime {},
+}
+
+/// The subset of a chain spec we care about.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ChainSpec {
+    pub name: String,
+    pub id: String,
+    genesis: Genesis,
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, ChainSpecError> {
+    hex::decode(strip_hex_prefix(s)).map_err(|e| ChainSpecError::InvalidHex(s.to_string(), e))
+}
+
+impl ChainSpec {
+    /// Loads the chain spec JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ChainSpecError> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+
+    /// Returns the raw genesis storage.
+    pub fn raw_genesis(&self) -> Result<&RawGenesis, ChainSpecError> {
+        match &self.genesis {
+            Genesis::Raw(raw) => Ok(raw),
+            Genesis::Runtime {} => Err(ChainSpecError::NotRaw),
+        }
+    }
+
+    /// Returns the runtime code stored under `:code`.
+    pub fn code(&self) -> Result<Vec<u8>, ChainSpecError> {
+        let code = self
+            .raw_genesis()?
+            .top
+            .iter()
+            .find(|(key, _)| strip_hex_prefix(key) == CODE_KEY)
+            .map(|(_, code)| code)
+            .ok_or(ChainSpecError::MissingCode)?;
+        decode_hex(code)
+    }
+
+    /// Returns the genesis key/value pairs of the top trie, the key is in hex without `0x`.
+    pub fn top_pairs(&self) -> Result<Vec<StoragePair>, ChainSpecError> {
+        self.raw_genesis()?
+            .top
+            .iter()
+            .map(|(key, value)| {
+                let key = strip_hex_prefix(key).to_lowercase();
+                Ok((key, Some(decode_hex(value)?)))
+            })
+            .collect()
+    }
+
+    /// Returns the genesis `(child_info, key, value)` triples of the default child tries.
+    pub fn child_triples(&self) -> Result<Vec<ChildTriple>, ChainSpecError> {
+        let mut triples = Vec::new();
+        for (child_info, pairs) in &self.raw_genesis()?.children_default {
+            let child_info = decode_hex(child_info)?;
+            for (key, value) in pairs {
+                triples.push((child_info.clone(), decode_hex(key)?, decode_hex(value)?));
+            }
+        }
+        Ok(triples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW_CHAIN_SPEC: &str = r#"{
+        "name": "Development",
+        "id": "dev",
+        "bootNodes": [],
+        "genesis": {
+            "raw": {
+                "top": {
+                    "0x3a636f6465": "0x0061736d01000000",
+                    "0x26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da932a5935f6edc617ae178fef9eb1e211fbe5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f": "0x0100000008"
+                },
+                "childrenDefault": {}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn parse_raw_chain_spec_should_work() {
+        let chain_spec: ChainSpec = serde_json::from_str(RAW_CHAIN_SPEC).unwrap();
+        assert_eq!(chain_spec.id, "dev");
+        assert_eq!(chain_spec.code().unwrap(), b"\0asm\x01\0\0\0".to_vec());
+
+        let pairs = chain_spec.top_pairs().unwrap();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].1, Some(vec![1, 0, 0, 0, 8]));
+        assert_eq!(pairs[1].0, CODE_KEY);
+    }
+
+    #[test]
+    fn non_raw_chain_spec_should_fail() {
+        let chain_spec: ChainSpec = serde_json::from_str(
+            r#"{"name": "Development", "id": "dev", "genesis": {"runtime": {"system": {}}}}"#,
+        )
+        .unwrap();
+        assert!(matches!(chain_spec.code(), Err(ChainSpecError::NotRaw)));
+    }
+}
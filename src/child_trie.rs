@@ -0,0 +1,234 @@
+//! Child trie decoding.
+//!
+//! The child tries are rooted at `:child_storage:default:<id>` in the top trie. The id is
+//! usually a hash, so the owner can not be inferred from the id itself, instead the ids of
+//! the known child trie layouts are registered in [`ChildTrieRegistry`].
+
+use std::collections::HashMap;
+
+use codec::Encode;
+use serde::Serialize;
+
+use crate::decode_storage_key_value::{DecodedKey, DecodedStorage};
+use crate::decoder::ValueDecoder;
+use crate::value::Value;
+use crate::well_known_keys::{WellKnownKey, DEFAULT_CHILD_STORAGE_KEY_PREFIX};
+
+/// Owner of a child trie and the layout of its key/value.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ChildTrieOwner {
+    pub pallet: String,
+    /// Human readable name of the child trie, e.g., `fund 3` of Crowdloan.
+    pub name: String,
+    /// Type of the child trie key, `None` if the key is hashed.
+    #[serde(skip)]
+    pub key_ty: Option<String>,
+    /// Type of the child trie value, `None` if the value is raw bytes.
+    #[serde(skip)]
+    pub value_ty: Option<String>,
+}
+
+/// Decoded key of a child trie.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ChildStorageKey {
+    #[serde(serialize_with = "crate::value::serialize_hex")]
+    pub child_trie_id: Vec<u8>,
+    /// `None` if the child trie is not registered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<ChildTrieOwner>,
+    #[serde(serialize_with = "crate::value::serialize_hex")]
+    pub key: Vec<u8>,
+    /// `None` if the key type is unknown or the key is hashed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded_key: Option<Value>,
+}
+
+impl ChildStorageKey {
+    /// Returns the type of the child trie value.
+    pub fn value_ty(&self) -> &str {
+        self.owner
+            .as_ref()
+            .and_then(|owner| owner.value_ty.as_deref())
+            .unwrap_or("Vec<u8>")
+    }
+}
+
+impl std::fmt::Display for ChildStorageKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.owner {
+            Some(owner) => write!(f, "{}[{}]", owner.pallet, owner.name)?,
+            None => write!(f, "child[0x{}]", hex::encode(&self.child_trie_id))?,
+        }
+        match &self.decoded_key {
+            Some(key) => write!(f, "({})", key),
+            None => write!(f, "(0x{})", hex::encode(&self.key)),
+        }
+    }
+}
+
+/// Returns the child trie id given either the id or the child storage key in the top trie.
+pub fn child_trie_id(child_info: &[u8]) -> &[u8] {
+    if child_info.starts_with(DEFAULT_CHILD_STORAGE_KEY_PREFIX) {
+        &child_info[DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..]
+    } else {
+        child_info
+    }
+}
+
+/// Returns the child trie id of the crowdloan fund `fund_index`.
+///
+/// Same as `polkadot_runtime_common::crowdloan::Module::id_from_index`.
+pub fn crowdloan_child_trie_id(fund_index: u32) -> Vec<u8> {
+    let mut buf = b"crowdloan".to_vec();
+    buf.extend_from_slice(&fund_index.encode());
+    sp_core::blake2_256(&buf).to_vec()
+}
+
+/// Registry of the known child tries.
+#[derive(Clone, Debug, Default)]
+pub struct ChildTrieRegistry {
+    owners: HashMap<Vec<u8>, ChildTrieOwner>,
+}
+
+impl ChildTrieRegistry {
+    /// Registers the child trie `child_trie_id`.
+    pub fn insert(&mut self, child_trie_id: Vec<u8>, owner: ChildTrieOwner) {
+        self.owners.insert(child_trie_id, owner);
+    }
+
+    /// Registers the child tries of crowdloan funds, the contributions are stored as
+    /// `who => (balance, memo)`.
+    pub fn register_crowdloan_funds<I: IntoIterator<Item = u32>>(&mut self, fund_indices: I) {
+        for fund_index in fund_indices {
+            self.insert(
+                crowdloan_child_trie_id(fund_index),
+                ChildTrieOwner {
+                    pallet: "Crowdloan".into(),
+                    name: format!("fund {}", fund_index),
+                    key_ty: Some("AccountId".into()),
+                    value_ty: Some("(Balance, Vec<u8>)".into()),
+                },
+            );
+        }
+    }
+
+    /// Registers the child trie of a contract, the trie id can be found in `Contracts::ContractInfoOf`.
+    ///
+    /// The keys are hashed by blake2_256 and the values are raw bytes.
+    pub fn register_contract(&mut self, trie_id: Vec<u8>) {
+        let name = format!("0x{}", hex::encode(&trie_id));
+        self.insert(
+            trie_id,
+            ChildTrieOwner {
+                pallet: "Contracts".into(),
+                name,
+                key_ty: None,
+                value_ty: None,
+            },
+        );
+    }
+
+    /// Returns the owner of the child trie given the child trie id or the child storage key.
+    pub fn owner(&self, child_info: &[u8]) -> Option<&ChildTrieOwner> {
+        self.owners.get(child_trie_id(child_info))
+    }
+
+    /// Fills the owner of the child trie root in the top trie.
+    pub fn annotate(&self, decoded: &mut DecodedStorage) {
+        if let DecodedKey::WellKnown(WellKnownKey::ChildStorage { id, owner, .. }) =
+            &mut decoded.key
+        {
+            *owner = self.owner(id).cloned();
+        }
+    }
+
+    /// Decodes a key/value pair of the child trie `child_info`, which is either the child trie id
+    /// or the child storage key in the top trie.
+    pub fn decode_child_storage(
+        &self,
+        decoder: &ValueDecoder,
+        child_info: &[u8],
+        key: Vec<u8>,
+        encoded_value: Option<Vec<u8>>,
+    ) -> DecodedStorage {
+        let owner = self.owner(child_info).cloned();
+
+        let decoded_key = owner
+            .as_ref()
+            .and_then(|owner| owner.key_ty.as_ref())
+            .and_then(|key_ty| decoder.decode_all(key_ty, &key).ok());
+
+        let value_ty = owner.as_ref().and_then(|owner| owner.value_ty.clone());
+        let value = encoded_value.and_then(|encoded| match value_ty {
+            Some(value_ty) => decoder
+                .decode_all(&value_ty, &encoded)
+                .map_err(|e| eprintln!("ERROR: failed to decode {}: {}", value_ty, e))
+                .ok(),
+            None => Some(Value::Bytes(encoded)),
+        });
+
+        DecodedStorage {
+            key: DecodedKey::Child(ChildStorageKey {
+                child_trie_id: child_trie_id(child_info).to_vec(),
+                owner,
+                key,
+                decoded_key,
+            }),
+            value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_crowdloan_child_storage_should_work() {
+        let mut registry = ChildTrieRegistry::default();
+        registry.register_crowdloan_funds(0..4);
+
+        let mut child_info = DEFAULT_CHILD_STORAGE_KEY_PREFIX.to_vec();
+        child_info.extend(crowdloan_child_trie_id(2));
+
+        let who = [7u8; 32];
+        let decoded = registry.decode_child_storage(
+            &ValueDecoder::default(),
+            &child_info,
+            who.to_vec(),
+            Some((100u128, b"memo".to_vec()).encode()),
+        );
+
+        match decoded.key {
+            DecodedKey::Child(key) => {
+                assert_eq!(key.child_trie_id, crowdloan_child_trie_id(2));
+                assert_eq!(key.owner.unwrap().name, "fund 2");
+                assert_eq!(key.decoded_key, Some(Value::Bytes(who.to_vec())));
+            }
+            _ => panic!("Not child storage"),
+        }
+        assert_eq!(
+            decoded.value,
+            Some(Value::Sequence(vec![
+                Value::U128(100),
+                Value::Bytes(b"memo".to_vec())
+            ]))
+        );
+    }
+
+    #[test]
+    fn unknown_child_trie_should_keep_raw_value() {
+        let registry = ChildTrieRegistry::default();
+        let decoded = registry.decode_child_storage(
+            &ValueDecoder::default(),
+            &[1, 2],
+            vec![3],
+            Some(vec![4]),
+        );
+        assert_eq!(decoded.value, Some(Value::Bytes(vec![4])));
+        assert!(matches!(
+            decoded.key,
+            DecodedKey::Child(ChildStorageKey { owner: None, .. })
+        ));
+    }
+}
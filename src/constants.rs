@@ -0,0 +1,129 @@
+//! Decodes the pallet constants in metadata, e.g., `Balances::ExistentialDeposit`.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::decoder::{DecodeError, ValueDecoder};
+use crate::metadata::{Metadata, MetadataError, ModuleConstantMetadata};
+use crate::value::Value;
+
+/// Error of decoding a constant.
+#[derive(Debug, thiserror::Error)]
+pub enum ConstantError {
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+}
+
+/// Decoded pallet constant.
+#[derive(Clone, Debug, Serialize)]
+pub struct DecodedConstant {
+    pub pallet: String,
+    pub name: String,
+    pub ty: String,
+    /// `None` if the constant type is unsupported yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub documentation: Vec<String>,
+}
+
+impl fmt::Display for DecodedConstant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}::{} => {}", self.pallet, self.name, value),
+            None => write!(f, "{}::{} => <{}>", self.pallet, self.name, self.ty),
+        }
+    }
+}
+
+fn decode(
+    decoder: &ValueDecoder,
+    pallet: &str,
+    constant: &ModuleConstantMetadata,
+) -> DecodedConstant {
+    let value = decoder
+        .decode_all(&constant.ty, constant.value())
+        .map_err(|e| {
+            eprintln!(
+                "ERROR: failed to decode {}::{}: {}",
+                pallet, constant.name, e
+            )
+        })
+        .ok();
+    DecodedConstant {
+        pallet: pallet.into(),
+        name: constant.name.clone(),
+        ty: constant.ty.clone(),
+        value,
+        documentation: constant.documentation().to_vec(),
+    }
+}
+
+/// Decodes the value of the constant `pallet::name`.
+pub fn decode_constant(
+    metadata: &Metadata,
+    decoder: &ValueDecoder,
+    pallet: &str,
+    name: &str,
+) -> Result<Value, ConstantError> {
+    let constant = metadata.module(pallet)?.constant(name)?;
+    Ok(decoder.decode_all(&constant.ty, constant.value())?)
+}
+
+/// Decodes all the constants of `pallet`, or of all the pallets if `None`, sorted by the
+/// pallet and constant names.
+pub fn decode_constants(
+    metadata: &Metadata,
+    decoder: &ValueDecoder,
+    pallet: Option<&str>,
+) -> Result<Vec<DecodedConstant>, ConstantError> {
+    let modules = match pallet {
+        Some(pallet) => vec![metadata.module(pallet)?],
+        None => metadata.modules.values().collect(),
+    };
+    let mut decoded = modules
+        .into_iter()
+        .flat_map(|module| {
+            module
+                .constants
+                .values()
+                .map(move |constant| decode(decoder, &module.name, constant))
+        })
+        .collect::<Vec<_>>();
+    decoded.sort_by(|a, b| (&a.pallet, &a.name).cmp(&(&b.pallet, &b.name)));
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::test_metadata;
+
+    #[test]
+    fn decode_constants_should_work() {
+        let metadata = test_metadata();
+        let decoder = ValueDecoder::default();
+
+        assert!(matches!(
+            decode_constant(&metadata, &decoder, "Balances", "ExistentialDeposit"),
+            Ok(Value::U128(_))
+        ));
+        assert!(matches!(
+            decode_constant(&metadata, &decoder, "Staking", "BondingDuration"),
+            Ok(Value::U32(_))
+        ));
+        assert!(matches!(
+            decode_constant(&metadata, &decoder, "Balances", "Unknown"),
+            Err(ConstantError::Metadata(MetadataError::ConstantNotFound(_)))
+        ));
+
+        let balances = decode_constants(&metadata, &decoder, Some("Balances")).unwrap();
+        assert!(balances
+            .iter()
+            .any(|constant| constant.name == "ExistentialDeposit" && constant.value.is_some()));
+        assert!(decode_constants(&metadata, &decoder, None).unwrap().len() > balances.len());
+    }
+}
@@ -1,8 +1,15 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
+use crate::child_trie::ChildStorageKey;
+use crate::decoder::ValueDecoder;
+use crate::key_preimages::KeyPreimages;
 use crate::metadata::{Metadata, StorageMetadata};
-use codec::{Decode, Encode};
+use crate::state_source::StoragePair;
+use crate::storage_prefix::storage_prefix;
+use crate::value::Value;
+use crate::well_known_keys::WellKnownKey;
 use frame_metadata::{DecodeDifferent, StorageEntryType, StorageHasher};
+use serde::Serialize;
 
 ////////////////////////////////////////////////////////////////////////
 //    Storage Key/Value decode
@@ -14,32 +21,50 @@ pub const PREFIX_LENGTH: usize = 32 * 2;
 
 /// Map of StorageKey prefix (module_prefix++storage_prefix) in hex string to StorageMetadata.
 ///
-/// So that we can know about the StorageMetadata given a complete StorageKey.
-pub struct StorageMetadataLookupTable(pub HashMap<String, StorageMetadata>);
+/// So that we can know about the StorageMetadata given a complete StorageKey. The keys of the
+/// opaque hashers are recovered from the candidate keys in [`KeyPreimages`], if any, and the
+/// keys of the concat hashers are verified by re-hashing them if enabled.
+pub struct StorageMetadataLookupTable {
+    pub storage: HashMap<String, StorageMetadata>,
+    /// Module name to the module prefix of its storage, which differ for the instanced
+    /// pallets, e.g., `Council` and `Instance1Collective`.
+    module_prefixes: HashMap<String, String>,
+    preimages: KeyPreimages,
+    verify_keys: bool,
+}
 
 impl From<Metadata> for StorageMetadataLookupTable {
     fn from(metadata: Metadata) -> Self {
-        Self(
-            metadata
-                .modules
-                .into_iter()
-                .map(|(_, module_metadata)| {
-                    module_metadata
-                        .storage
-                        .into_iter()
-                        .map(|(_, storage_metadata)| {
-                            let storage_prefix = storage_metadata.prefix();
-                            (hex::encode(storage_prefix.0), storage_metadata)
-                        })
-                })
-                .flatten()
-                .collect(),
-        )
+        let mut module_prefixes = HashMap::new();
+        let storage = metadata
+            .modules
+            .into_values()
+            .flat_map(|module_metadata| {
+                if let Some(storage_metadata) = module_metadata.storage.values().next() {
+                    module_prefixes.insert(
+                        module_metadata.name.clone(),
+                        storage_metadata.module_prefix.clone(),
+                    );
+                }
+                module_metadata
+                    .storage
+                    .into_values()
+                    .map(|storage_metadata| {
+                        let storage_prefix = storage_metadata.prefix();
+                        (hex::encode(storage_prefix.0), storage_metadata)
+                    })
+            })
+            .collect();
+        Self {
+            module_prefixes,
+            ..Self::new(storage)
+        }
     }
 }
 
 // Transparent type of decoded StorageKey.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind")]
 pub enum TransparentStorageType {
     Plain {
         /// "u32"
@@ -47,30 +72,105 @@ pub enum TransparentStorageType {
     },
     Map {
         /// value of key, e.g, "be5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f" for T::AccountId
+        #[serde(serialize_with = "crate::value::serialize_hex_str")]
         key: String,
         /// type of value, e.g., "AccountInfo<T::Index, T::AccountData>"
         value_ty: String,
     },
     DoubleMap {
+        #[serde(serialize_with = "crate::value::serialize_hex_str")]
         key1: String,
         key1_ty: String,
+        #[serde(serialize_with = "crate::value::serialize_hex_str")]
         key2: String,
         key2_ty: String,
         value_ty: String,
     },
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl TransparentStorageType {
+    /// Returns the type of storage value.
+    pub fn value_ty(&self) -> &str {
+        match self {
+            Self::Plain { value_ty } => value_ty,
+            Self::Map { value_ty, .. } => value_ty,
+            Self::DoubleMap { value_ty, .. } => value_ty,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct TransparentStorageKey {
     pub module_prefix: String,
     pub storage_prefix: String,
     pub ty: TransparentStorageType,
 }
 
+impl fmt::Display for TransparentStorageKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}::{}", self.module_prefix, self.storage_prefix)?;
+        match &self.ty {
+            TransparentStorageType::Plain { .. } => Ok(()),
+            TransparentStorageType::Map { key, .. } => write!(f, "(0x{})", key),
+            TransparentStorageType::DoubleMap { key1, key2, .. } => {
+                write!(f, "(0x{}, 0x{})", key1, key2)
+            }
+        }
+    }
+}
+
+/// Decoded storage key, a well-known key, a pallet storage key or a child trie key.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum DecodedKey {
+    WellKnown(WellKnownKey),
+    Storage(TransparentStorageKey),
+    Child(ChildStorageKey),
+}
+
+impl DecodedKey {
+    /// Returns the type of storage value.
+    pub fn value_ty(&self) -> &str {
+        match self {
+            Self::WellKnown(key) => key.value_ty(),
+            Self::Storage(key) => key.ty.value_ty(),
+            Self::Child(key) => key.value_ty(),
+        }
+    }
+}
+
+impl fmt::Display for DecodedKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WellKnown(key) => key.fmt(f),
+            Self::Storage(key) => key.fmt(f),
+            Self::Child(key) => key.fmt(f),
+        }
+    }
+}
+
+/// Decoded storage key/value pair.
+#[derive(Clone, Debug, Serialize)]
+pub struct DecodedStorage {
+    pub key: DecodedKey,
+    /// `None` if the value is absent or the value type is unsupported yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+impl fmt::Display for DecodedStorage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{} => {}", self.key, value),
+            None => write!(f, "{} => <{}>", self.key, self.key.value_ty()),
+        }
+    }
+}
+
 /// Converts the inner of `DecodeDifferent::Decoded(_)` to String.
 fn as_decoded_type<B: 'static, O: 'static + Into<String>>(value: DecodeDifferent<B, O>) -> String {
     match value {
-        DecodeDifferent::Encode(b) => unreachable!("TODO: really unreachable?"),
+        DecodeDifferent::Encode(_) => unreachable!("TODO: really unreachable?"),
         DecodeDifferent::Decoded(o) => o.into(),
     }
 }
@@ -87,14 +187,67 @@ fn build_transparent_storage_key(
 }
 
 impl StorageMetadataLookupTable {
+    /// Creates the lookup table of `storage`, keyed by the storage prefix in hex.
+    pub fn new(storage: HashMap<String, StorageMetadata>) -> Self {
+        Self {
+            storage,
+            module_prefixes: HashMap::new(),
+            preimages: KeyPreimages::default(),
+            verify_keys: false,
+        }
+    }
+
+    /// Sets the candidate keys to recover the keys of the opaque hashers.
+    pub fn set_preimages(&mut self, preimages: KeyPreimages) {
+        self.preimages = preimages;
+    }
+
+    /// Sets whether to verify the keys of the concat hashers by re-hashing them, the keys whose
+    /// hashed keys mismatch are rejected, e.g., the keys of a corrupt dump or the wrong metadata.
+    pub fn set_verify_keys(&mut self, verify: bool) {
+        self.verify_keys = verify;
+    }
+
     /// Returns the StorageMetadata given the `prefix` of a StorageKey.
     pub fn lookup(&self, prefix: &str) -> Option<&StorageMetadata> {
-        self.0.get(prefix)
+        self.storage.get(prefix)
+    }
+
+    /// Returns the StorageMetadata of `pallet::item`, `pallet` is either the module name or the
+    /// module prefix of its storage, e.g., `Council` or `Instance1Collective`.
+    pub fn storage_metadata(&self, pallet: &str, item: &str) -> Option<&StorageMetadata> {
+        let module_prefix = self
+            .module_prefixes
+            .get(pallet)
+            .map_or(pallet, String::as_str);
+        self.lookup(&hex::encode(storage_prefix(module_prefix, item)))
+    }
+
+    /// Returns the documentation of the storage item of `key` in metadata.
+    pub fn documentation(&self, key: &TransparentStorageKey) -> &[String] {
+        let prefix = storage_prefix(&key.module_prefix, &key.storage_prefix);
+        self.lookup(&hex::encode(prefix))
+            .map(StorageMetadata::documentation)
+            .unwrap_or(&[])
     }
 
     /// Converts `storage_key` in hex string to a _readable_ format.
     pub fn parse_storage_key(&self, storage_key: String) -> Option<TransparentStorageKey> {
-        let storage_prefix = &storage_key[..PREFIX_LENGTH];
+        if let Some(well_known) = WellKnownKey::from_hex(&storage_key) {
+            eprintln!(
+                "ERROR: {} is a well-known key instead of a pallet storage key",
+                well_known
+            );
+            return None;
+        }
+        if storage_key.len() < PREFIX_LENGTH {
+            eprintln!(
+                "ERROR: storage key {} is shorter than the prefix",
+                storage_key
+            );
+            return None;
+        }
+        let storage_prefix = storage_key.get(..PREFIX_LENGTH)?;
 
         if let Some(storage_metadata) = self.lookup(storage_prefix) {
             match &storage_metadata.ty {
@@ -105,30 +258,20 @@ impl StorageMetadataLookupTable {
                         value_ty: as_decoded_type(value.clone()),
                     },
                 }),
-                StorageEntryType::Map {
-                    hasher,
-                    key,
-                    value,
-                    unused,
-                } => match hasher {
-                    StorageHasher::Twox64Concat | StorageHasher::Blake2_128Concat => {
-                        let hashed_key_concat = &storage_key[PREFIX_LENGTH..];
-                        let hash_length = hash_length_of(hasher);
-                        let _hashed_key = &hashed_key_concat[..hash_length];
-                        let key = &hashed_key_concat[hash_length..];
-
-                        let transparent_ty = TransparentStorageType::Map {
-                            key: key.into(),
-                            value_ty: as_decoded_type(value.clone()),
-                        };
-
-                        Some(build_transparent_storage_key(
-                            &storage_metadata,
-                            transparent_ty,
-                        ))
-                    }
-                    _ => unreachable!("All Map storage should use foo_concat hasher"),
-                },
+                StorageEntryType::Map { hasher, value, .. } => {
+                    let hashed_key_concat = storage_key.get(PREFIX_LENGTH..)?;
+                    let (key, _) = self.split_key(hasher, hashed_key_concat, None)?;
+
+                    let transparent_ty = TransparentStorageType::Map {
+                        key,
+                        value_ty: as_decoded_type(value.clone()),
+                    };
+
+                    Some(build_transparent_storage_key(
+                        storage_metadata,
+                        transparent_ty,
+                    ))
+                }
                 StorageEntryType::DoubleMap {
                     hasher,
                     key1,
@@ -137,61 +280,165 @@ impl StorageMetadataLookupTable {
                     key2_hasher,
                 } => {
                     // hashed_key1 ++ key1 ++ hashed_key2 ++ key2
-                    let hashed_key_concat = &storage_key[PREFIX_LENGTH..];
-                    match hasher {
-                        StorageHasher::Twox64Concat | StorageHasher::Blake2_128Concat => {
-                            let key1_hash_length = hash_length_of(hasher);
-
-                            // key1 ++ hashed_key2 ++ key2
-                            let key1_hashed_key2_key2 = &hashed_key_concat[key1_hash_length..];
-
-                            let key1_ty = as_decoded_type(key1.clone());
-
-                            if let Some(key1_length) = get_key1_length(key1_ty.clone()) {
-                                let key1 = &key1_hashed_key2_key2[..key1_length];
-                                let hashed_key2_key2 = &key1_hashed_key2_key2[key1_length..];
-
-                                match key2_hasher {
-                                    StorageHasher::Twox64Concat
-                                    | StorageHasher::Blake2_128Concat => {
-                                        let key2_hash_length = hash_length_of(key2_hasher);
-                                        let raw_key2 = &hashed_key2_key2[key2_hash_length..];
-
-                                        let key2_ty = as_decoded_type(key2.clone());
-
-                                        let transparent_ty = TransparentStorageType::DoubleMap {
-                                            key1: key1.into(),
-                                            key1_ty,
-                                            key2: raw_key2.into(),
-                                            key2_ty,
-                                            value_ty: as_decoded_type(value.clone()),
-                                        };
-
-                                        Some(build_transparent_storage_key(
-                                            &storage_metadata,
-                                            transparent_ty,
-                                        ))
-                                    }
-                                    _ => unreachable!(
-                                    "All DoubleMap storage should use foo_concat hasher for key2"
-                                ),
-                                }
-                            } else {
-                                println!("ERROR: can not infer the length of key1");
-                                None
+                    let hashed_key_concat = storage_key.get(PREFIX_LENGTH..)?;
+                    let key1_ty = as_decoded_type(key1.clone());
+                    let key2_ty = as_decoded_type(key2.clone());
+
+                    // The length of key1 is only required by the hashers keeping the raw key.
+                    let key1_length = match hasher {
+                        StorageHasher::Twox64Concat
+                        | StorageHasher::Blake2_128Concat
+                        | StorageHasher::Identity => match get_key1_length(key1_ty.clone()) {
+                            Some(key1_length) => Some(key1_length),
+                            None => {
+                                eprintln!("ERROR: can not infer the length of key1");
+                                return None;
                             }
-                        }
-                        _ => unreachable!(
-                            "All DoubleMap storage should use foo_concat hasher for key1"
-                        ),
-                    }
+                        },
+                        _ => None,
+                    };
+                    let (key1, hashed_key2_key2) =
+                        self.split_key(hasher, hashed_key_concat, key1_length)?;
+                    let (key2, _) = self.split_key(key2_hasher, hashed_key2_key2, None)?;
+
+                    let transparent_ty = TransparentStorageType::DoubleMap {
+                        key1,
+                        key1_ty,
+                        key2,
+                        key2_ty,
+                        value_ty: as_decoded_type(value.clone()),
+                    };
+
+                    Some(build_transparent_storage_key(
+                        storage_metadata,
+                        transparent_ty,
+                    ))
                 }
             }
         } else {
-            println!("ERROR: can not find the StorageMetadata from lookup table");
+            eprintln!("ERROR: can not find the StorageMetadata from lookup table");
             None
         }
     }
+
+    /// Splits the raw key of `hasher` off `hashed_key_concat`, both in hex string.
+    ///
+    /// The raw key of the concat hashers follows the hashed key, which is the rest if
+    /// `key_length` is `None`, and is verified by re-hashing it if enabled. The Identity hasher
+    /// is the raw key itself, i.e., the concat hasher with an empty hash. The raw key of the
+    /// opaque hashers is looked up in the key preimages.
+    fn split_key<'a>(
+        &self,
+        hasher: &StorageHasher,
+        hashed_key_concat: &'a str,
+        key_length: Option<usize>,
+    ) -> Option<(String, &'a str)> {
+        match hasher {
+            StorageHasher::Twox64Concat
+            | StorageHasher::Blake2_128Concat
+            | StorageHasher::Identity => {
+                let hash_length = hash_length_of(hasher);
+                let key_concat = hashed_key_concat.get(hash_length..)?;
+                let key_length = key_length.unwrap_or(key_concat.len());
+                let key = key_concat.get(..key_length)?;
+                if self.verify_keys {
+                    let hashed_key = &hashed_key_concat[..hash_length];
+                    let rehashed_key = hex::decode(key)
+                        .map(|key| hex::encode(StorageMetadata::hash(hasher, &key)))
+                        .unwrap_or_default();
+                    if !rehashed_key.starts_with(hashed_key.to_lowercase().as_str()) {
+                        eprintln!(
+                            "ERROR: hashed key {} mismatches the {:?} hash of key {}",
+                            hashed_key, hasher, key
+                        );
+                        return None;
+                    }
+                }
+                Some((key.into(), &key_concat[key_length..]))
+            }
+            _ => {
+                let hash_length = hash_length_of(hasher);
+                let hashed_key = hashed_key_concat.get(..hash_length)?;
+                let key = hex::decode(hashed_key)
+                    .ok()
+                    .and_then(|hashed_key| self.preimages.lookup(&hashed_key));
+                match key {
+                    Some(key) => Some((hex::encode(key), &hashed_key_concat[hash_length..])),
+                    None => {
+                        eprintln!(
+                            "ERROR: can not find the preimage of the hashed key {}",
+                            hashed_key
+                        );
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Decodes a storage key/value pair, `storage_key` is in hex string.
+    ///
+    /// The value is decoded by `decoder` dynamically and falls back to the decoders
+    /// using the concrete runtime types.
+    pub fn decode_storage(
+        &self,
+        decoder: &ValueDecoder,
+        storage_key: String,
+        encoded_value: Option<Vec<u8>>,
+    ) -> Option<DecodedStorage> {
+        if let Some(well_known) = WellKnownKey::from_hex(&storage_key) {
+            let value = encoded_value.and_then(|encoded| {
+                well_known
+                    .decode_value(decoder, &encoded)
+                    .map_err(|e| eprintln!("ERROR: failed to decode {}: {}", well_known, e))
+                    .ok()
+            });
+            return Some(DecodedStorage {
+                key: DecodedKey::WellKnown(well_known),
+                value,
+            });
+        }
+
+        let key = self.parse_storage_key(storage_key)?;
+        let value_ty = key.ty.value_ty();
+        let value =
+            encoded_value.and_then(|encoded| match decoder.decode_all(value_ty, &encoded) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    let value = try_decode_storage_value(value_ty, encoded);
+                    if value.is_none() {
+                        eprintln!("ERROR: failed to decode {}: {}", value_ty, e);
+                    }
+                    value
+                }
+            });
+        Some(DecodedStorage {
+            key: DecodedKey::Storage(key),
+            value,
+        })
+    }
+
+    /// Groups the storage key/value pairs by the storage prefix, i.e., one group per
+    /// storage item. The pairs whose prefix is not in the lookup table are dropped.
+    pub fn group_by_prefix<I>(&self, pairs: I) -> HashMap<String, Vec<StoragePair>>
+    where
+        I: IntoIterator<Item = StoragePair>,
+    {
+        let mut groups: HashMap<String, Vec<_>> = HashMap::new();
+        for (key, value) in pairs {
+            let prefix = match key.get(..PREFIX_LENGTH) {
+                Some(prefix) => prefix,
+                None => continue,
+            };
+            if self.storage.contains_key(prefix) {
+                groups
+                    .entry(prefix.to_string())
+                    .or_default()
+                    .push((key, value));
+            }
+        }
+        groups
+    }
 }
 
 /// TODO: ensure all key1 in DoubleMap are included in this table.
@@ -235,12 +482,13 @@ fn hash_length_of(hasher: &StorageHasher) -> usize {
         StorageHasher::Twox128 => 32,
         StorageHasher::Twox256 => 32 * 2,
         StorageHasher::Twox64Concat => 16,
-        StorageHasher::Identity => unreachable!(),
+        StorageHasher::Identity => 0,
     }
 }
 
+#[cfg(feature = "runtime")]
 fn generic_decode<T: codec::Decode>(encoded: Vec<u8>) -> Result<T, codec::Error> {
-    Decode::decode(&mut encoded.as_slice())
+    codec::Decode::decode(&mut encoded.as_slice())
 }
 
 // TODO: use a script to generate this function automatically.
@@ -262,72 +510,62 @@ fn generic_decode<T: codec::Decode>(encoded: Vec<u8>) -> Result<T, codec::Error>
 // "AccountInfo<T::Index, T::AccountData>",
 // "AccountStatus<BalanceOf<T>>", "ActiveEraInfo", "BalanceOf<T>", "DigestOf<T>", "ElectionResult<T::AccountId, BalanceOf<T>>", "ElectionScore", "ElectionStatus<T::BlockNumber>", "EraIndex", "EraRewardPoints<T::AccountId>", "EthereumAddress", "EventIndex", "Exposure<T::AccountId, BalanceOf<T>>", "Forcing", "LastRuntimeUpgradeInfo", "MaybeRandomness", "Multiplier", "Multisig<T::BlockNumber, BalanceOf<T>, T::AccountId>", "NextConfigDescriptor", "Nominations<T::AccountId>", "OffenceDetails<T::AccountId, T::IdentificationTuple>", "OpenTip<T::AccountId, BalanceOf<T>, T::BlockNumber, T::Hash>", "Perbill", "Phase", "PreimageStatus<T::AccountId, BalanceOf<T>, T::BlockNumber>", "PropIndex", "Proposal<T::AccountId, BalanceOf<T>>", "ProposalIndex", "ReferendumIndex", "ReferendumInfo<T::BlockNumber, T::Hash, BalanceOf<T>>", "Registration<BalanceOf<T>>", "Releases", "RewardDestination", "SessionIndex", "SetId", "StakingLedger<T::AccountId, BalanceOf<T>>", "StatementKind", "StoredPendingChange<T::BlockNumber>", "StoredState<T::BlockNumber>", "T::AccountId", "T::Balance", "T::BlockNumber", "T::Hash", "T::Keys", "T::Moment", "T::ValidatorId", "TaskAddress<T::BlockNumber>", "ValidatorPrefs", "Vec<(AuthorityId, BabeAuthorityWeight)>", "Vec<(EraIndex, SessionIndex)>", "Vec<(PropIndex, T::Hash, T::AccountId)>", "Vec<(T::AccountId, BalanceOf<T>)>", "Vec<(T::BlockNumber, EventIndex)>", "Vec<(T::ValidatorId, T::Keys)>", "Vec<BalanceLock<T::Balance>>", "Vec<DeferredOffenceOf<T>>", "Vec<EventRecord<T::Event, T::Hash>>", "Vec<Option<RegistrarInfo<BalanceOf<T>, T::AccountId>>>", "Vec<Option<Scheduled<<T as Trait>::Call, T::BlockNumber, T::\nPalletsOrigin, T::AccountId>>>", "Vec<ProposalIndex>", "Vec<ReportIdOf<T>>", "Vec<T::AccountId>", "Vec<T::AuthorityId>", "Vec<T::BlockNumber>", "Vec<T::Hash>", "Vec<T::ValidatorId>", "Vec<UnappliedSlash<T::AccountId, BalanceOf<T>>>", "Vec<UncleEntryItem<T::BlockNumber, T::Hash, T::AccountId>>", "Vec<schnorrkel::Randomness>", "Vec<u32>", "Vec<u8>", "VestingInfo<BalanceOf<T>, T::BlockNumber>", "Votes<T::AccountId, T::BlockNumber>", "Voting<BalanceOf<T>, T::AccountId, T::BlockNumber>", "bool", "schnorrkel::Randomness", "slashing::SlashingSpans", "slashing::SpanRecord<BalanceOf<T>>", "u32", "u64", "weights::ExtrinsicsWeight"]
 
-fn try_decode_storage_value(any_ty: &str, encoded: Vec<u8>) {
+/// `AccountIndex` of the Polkadot runtime.
+#[cfg(feature = "runtime")]
+type AccountIndex = u32;
+/// `Balance` of the Polkadot runtime.
+#[cfg(feature = "runtime")]
+type Balance = u128;
+
+/// Decodes the storage value using the concrete runtime types, requires the `runtime` feature.
+#[cfg(feature = "runtime")]
+fn try_decode_storage_value(any_ty: &str, encoded: Vec<u8>) -> Option<Value> {
     use frame_system::AccountInfo;
     use pallet_balances::AccountData;
-    use polkadot_primitives::v1::{AccountIndex, Balance};
 
     match any_ty {
         "AccountInfo<T::Index, T::AccountData>" => {
-            let decoded =
-                generic_decode::<AccountInfo<AccountIndex, AccountData<Balance>>>(encoded);
-            println!("decoded value:{:?}", decoded);
-        }
-        _ => {
-            println!("Unknown value type: {:?}", any_ty);
+            generic_decode::<AccountInfo<AccountIndex, AccountData<Balance>>>(encoded)
+                .ok()
+                .map(|info| {
+                    Value::composite(vec![
+                        ("nonce", info.nonce.into()),
+                        ("refcount", info.refcount.into()),
+                        (
+                            "data",
+                            Value::composite(vec![
+                                ("free", info.data.free.into()),
+                                ("reserved", info.data.reserved.into()),
+                                ("misc_frozen", info.data.misc_frozen.into()),
+                                ("fee_frozen", info.data.fee_frozen.into()),
+                            ]),
+                        ),
+                    ])
+                })
         }
+        _ => None,
     }
 }
 
+#[cfg(not(feature = "runtime"))]
+fn try_decode_storage_value(_any_ty: &str, _encoded: Vec<u8>) -> Option<Value> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use frame_metadata::RuntimeMetadataPrefixed;
+    use crate::metadata::test_metadata;
+    use codec::Encode;
+    #[cfg(feature = "runtime")]
     use frame_system::AccountInfo;
+    #[cfg(feature = "runtime")]
     use pallet_balances::AccountData;
-    use polkadot_primitives::v1::{AccountIndex, Balance};
-    use std::convert::TryInto;
-
-    fn get_metadata() -> Metadata {
-        let s = include_str!("../test_data/metadata.txt");
-        let s = s.trim();
-        // string hex
-        // decode hex string without 0x prefix
-        let data = hex::decode(s).unwrap();
-        let meta: RuntimeMetadataPrefixed =
-            Decode::decode(&mut data.as_slice()).expect("failed to decode metadata prefixed");
-        meta.try_into().expect("failed to convert to metadata")
-    }
-
-    // Filter out (key1, key2) pairs of all DoubleMap.
-    fn filter_double_map() -> Vec<(String, String)> {
-        let metadata = get_metadata();
-        let double_map_keys = metadata
-            .modules
-            .into_iter()
-            .map(|(_, module_metadata)| {
-                module_metadata
-                    .storage
-                    .into_iter()
-                    .filter_map(|(_, storage_metadata)| {
-                        if let StorageEntryType::DoubleMap {
-                            ref key1, ref key2, ..
-                        } = storage_metadata.ty
-                        {
-                            let key1_ty = as_decoded_type(key1.clone());
-                            let key2_ty = as_decoded_type(key2.clone());
-                            Some((key1_ty, key2_ty))
-                        } else {
-                            None
-                        }
-                    })
-            })
-            .flatten()
-            .collect::<Vec<_>>();
 
-        double_map_keys
-    }
+    /// Encoded `AccountInfo { nonce: 1, refcount: 8, data: AccountData { free: 100, reserved: 200, misc_frozen: 300, fee_frozen: 400 } }`.
+    const MOCK_ACCOUNT_INFO: &str = "010000000864000000000000000000000000000000c80000000000000000000000000000002c01000000000000000000000000000090010000000000000000000000000000";
 
+    #[cfg(feature = "runtime")]
     fn get_value_type(ty: StorageEntryType) -> String {
         match ty {
             StorageEntryType::Plain(value) => as_decoded_type(value),
@@ -336,18 +574,18 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "runtime")]
     fn filter_storage_value_types() -> Vec<String> {
-        let metadata = get_metadata();
+        let metadata = test_metadata();
         let mut value_types = metadata
             .modules
-            .into_iter()
-            .map(|(_, module_metadata)| {
+            .into_values()
+            .flat_map(|module_metadata| {
                 module_metadata
                     .storage
-                    .into_iter()
-                    .map(|(_, storage_metadata)| get_value_type(storage_metadata.ty))
+                    .into_values()
+                    .map(|storage_metadata| get_value_type(storage_metadata.ty))
             })
-            .flatten()
             .collect::<Vec<_>>();
 
         value_types.sort();
@@ -355,7 +593,8 @@ mod tests {
         value_types
     }
 
-    // hex(encoded): 010000000864000000000000000000000000000000c80000000000000000000000000000002c01000000000000000000000000000090010000000000000000000000000000
+    // hex(encoded): MOCK_ACCOUNT_INFO
+    #[cfg(feature = "runtime")]
     fn mock_account_info_data() -> (Vec<u8>, AccountInfo<AccountIndex, AccountData<Balance>>) {
         let mock_account_data: AccountData<Balance> = AccountData {
             free: 100,
@@ -380,7 +619,7 @@ mod tests {
         //
         //      Account ID: 0xbe5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f
         // Blake2 128 Hash: 0x32a5935f6edc617ae178fef9eb1e211f
-        let metadata = get_metadata();
+        let metadata = test_metadata();
         let table: StorageMetadataLookupTable = metadata.into();
 
         let storage_key = "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da932a5935f6edc617ae178fef9eb1e211fbe5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f";
@@ -398,29 +637,37 @@ mod tests {
             table.parse_storage_key(storage_key.into()).unwrap(),
             expected
         );
-
-        // Firstly, we need to build Storage Value decode function table.
-        let mut storage_value_decode_fn_map = HashMap::new();
-
-        let try_decode_account_info = |encoded: Vec<u8>| {
-            generic_decode::<AccountInfo<AccountIndex, AccountData<Balance>>>(encoded)
-        };
-
-        // let try_decode_balance = |encoded: Vec<u8>| generic_decode::<Balance>(encoded);
-
-        storage_value_decode_fn_map.insert(
-            String::from("AccountInfo<T::Index, T::AccountData>"),
-            try_decode_account_info,
+        assert_eq!(
+            table.documentation(&expected),
+            [" The full account information for a particular account ID.".to_string()]
         );
 
-        let storage_value = "010000000864000000000000000000000000000000c80000000000000000000000000000002c01000000000000000000000000000090010000000000000000000000000000";
-        if let TransparentStorageType::Map { key, value_ty } = expected.ty {
-            let decode_fn = storage_value_decode_fn_map.get(&value_ty).unwrap();
-            let decoded_value = decode_fn(hex::decode(storage_value).unwrap()).unwrap();
-            let expected_decoded_value = mock_account_info_data().1;
-            assert_eq!(decoded_value, expected_decoded_value);
-        } else {
-            panic!("Not Map")
+        // Decoding the value using the runtime types requires the `runtime` feature.
+        #[cfg(feature = "runtime")]
+        {
+            // Firstly, we need to build Storage Value decode function table.
+            let mut storage_value_decode_fn_map = HashMap::new();
+
+            let try_decode_account_info = |encoded: Vec<u8>| {
+                generic_decode::<AccountInfo<AccountIndex, AccountData<Balance>>>(encoded)
+            };
+
+            // let try_decode_balance = |encoded: Vec<u8>| generic_decode::<Balance>(encoded);
+
+            storage_value_decode_fn_map.insert(
+                String::from("AccountInfo<T::Index, T::AccountData>"),
+                try_decode_account_info,
+            );
+
+            let storage_value = MOCK_ACCOUNT_INFO;
+            if let TransparentStorageType::Map { value_ty, .. } = expected.ty {
+                let decode_fn = storage_value_decode_fn_map.get(&value_ty).unwrap();
+                let decoded_value = decode_fn(hex::decode(storage_value).unwrap()).unwrap();
+                let expected_decoded_value = mock_account_info_data().1;
+                assert_eq!(decoded_value, expected_decoded_value);
+            } else {
+                panic!("Not Map")
+            }
         }
     }
 
@@ -431,7 +678,7 @@ mod tests {
         // key1 twox_64_concat SessionIndex
         // key2 twxo_64_concat T::ValidatorId
         let storage_key = "2b06af9719ac64d755623cda8ddd9b94b1c371ded9e9c565e89ba783c4d5f5f9b4def25cfda6ef3a00000000e535263148daaf49be5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f";
-        let metadata = get_metadata();
+        let metadata = test_metadata();
         let table: StorageMetadataLookupTable = metadata.into();
         println!("{:?}", table.parse_storage_key(storage_key.into()));
 
@@ -453,12 +700,202 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_opaque_hashed_key_should_work() {
+        let mut table: StorageMetadataLookupTable = test_metadata().into();
+        // Pretend the keys of System::Account and key1 of ImOnline::AuthoredBlocks use the
+        // opaque hashers.
+        let account_prefix = storage_prefix("System", "Account");
+        if let StorageEntryType::Map { hasher, .. } = &mut table
+            .storage
+            .get_mut(&hex::encode(&account_prefix))
+            .unwrap()
+            .ty
+        {
+            *hasher = StorageHasher::Blake2_256;
+        }
+        let authored_blocks_prefix = storage_prefix("ImOnline", "AuthoredBlocks");
+        if let StorageEntryType::DoubleMap { hasher, .. } = &mut table
+            .storage
+            .get_mut(&hex::encode(&authored_blocks_prefix))
+            .unwrap()
+            .ty
+        {
+            *hasher = StorageHasher::Twox128;
+        }
+
+        let account_key =
+            hex::encode([account_prefix, sp_core::blake2_256(&[1u8; 32]).to_vec()].concat());
+        let authored_blocks_key = hex::encode(
+            [
+                authored_blocks_prefix,
+                sp_core::twox_128(&2u32.encode()).to_vec(),
+                StorageMetadata::hash_key(&StorageHasher::Twox64Concat, &[1u8; 32]),
+            ]
+            .concat(),
+        );
+        assert!(table.parse_storage_key(account_key.clone()).is_none());
+
+        let mut preimages = KeyPreimages::default();
+        preimages.insert([1u8; 32].encode());
+        preimages.insert(2u32.encode());
+        table.set_preimages(preimages);
+
+        match table.parse_storage_key(account_key).unwrap().ty {
+            TransparentStorageType::Map { key, .. } => assert_eq!(key, hex::encode([1u8; 32])),
+            _ => panic!("Not Map"),
+        }
+        match table.parse_storage_key(authored_blocks_key).unwrap().ty {
+            TransparentStorageType::DoubleMap { key1, key2, .. } => {
+                assert_eq!(key1, "02000000");
+                assert_eq!(key2, hex::encode([1u8; 32]));
+            }
+            _ => panic!("Not DoubleMap"),
+        }
+    }
+
+    #[test]
+    fn parse_identity_hashed_key_should_work() {
+        let mut table: StorageMetadataLookupTable = test_metadata().into();
+        // Pretend the keys of System::Account and key1 of ImOnline::AuthoredBlocks use the
+        // Identity hasher.
+        let account_prefix = storage_prefix("System", "Account");
+        if let StorageEntryType::Map { hasher, .. } = &mut table
+            .storage
+            .get_mut(&hex::encode(&account_prefix))
+            .unwrap()
+            .ty
+        {
+            *hasher = StorageHasher::Identity;
+        }
+        let authored_blocks_prefix = storage_prefix("ImOnline", "AuthoredBlocks");
+        if let StorageEntryType::DoubleMap { hasher, .. } = &mut table
+            .storage
+            .get_mut(&hex::encode(&authored_blocks_prefix))
+            .unwrap()
+            .ty
+        {
+            *hasher = StorageHasher::Identity;
+        }
+        table.set_verify_keys(true);
+
+        let account_key = hex::encode([account_prefix, vec![1u8; 32]].concat());
+        match table.parse_storage_key(account_key).unwrap().ty {
+            TransparentStorageType::Map { key, .. } => assert_eq!(key, hex::encode([1u8; 32])),
+            _ => panic!("Not Map"),
+        }
+
+        let authored_blocks_key = hex::encode(
+            [
+                authored_blocks_prefix,
+                2u32.encode(),
+                StorageMetadata::hash_key(&StorageHasher::Twox64Concat, &[1u8; 32]),
+            ]
+            .concat(),
+        );
+        match table.parse_storage_key(authored_blocks_key).unwrap().ty {
+            TransparentStorageType::DoubleMap { key1, key2, .. } => {
+                assert_eq!(key1, "02000000");
+                assert_eq!(key2, hex::encode([1u8; 32]));
+            }
+            _ => panic!("Not DoubleMap"),
+        }
+    }
+
+    #[test]
+    fn verify_concat_hashed_key_should_work() {
+        let mut table: StorageMetadataLookupTable = test_metadata().into();
+        table.set_verify_keys(true);
+
+        // System::Account and ImOnline::AuthoredBlocks in the tests above.
+        let account_key = "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da932a5935f6edc617ae178fef9eb1e211fbe5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f";
+        let authored_blocks_key = "2b06af9719ac64d755623cda8ddd9b94b1c371ded9e9c565e89ba783c4d5f5f9b4def25cfda6ef3a00000000e535263148daaf49be5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f";
+        assert!(table.parse_storage_key(account_key.into()).is_some());
+        assert!(table
+            .parse_storage_key(authored_blocks_key.into())
+            .is_some());
+
+        // The last byte of the account id is corrupt.
+        let corrupt_account_key = format!("{}00", &account_key[..account_key.len() - 2]);
+        assert!(table
+            .parse_storage_key(corrupt_account_key.clone())
+            .is_none());
+        // The session index is corrupt.
+        let corrupt_authored_blocks_key = authored_blocks_key.replace("00000000e5", "01000000e5");
+        assert!(table
+            .parse_storage_key(corrupt_authored_blocks_key.clone())
+            .is_none());
+
+        table.set_verify_keys(false);
+        assert!(table.parse_storage_key(corrupt_account_key).is_some());
+        assert!(table
+            .parse_storage_key(corrupt_authored_blocks_key)
+            .is_some());
+    }
+
+    #[test]
+    fn decoded_storage_to_json_should_work() {
+        let table: StorageMetadataLookupTable = test_metadata().into();
+
+        let storage_key = "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da932a5935f6edc617ae178fef9eb1e211fbe5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f";
+        let decoded = table
+            .decode_storage(
+                &ValueDecoder::default(),
+                storage_key.into(),
+                Some(hex::decode(MOCK_ACCOUNT_INFO).unwrap()),
+            )
+            .unwrap();
+
+        let json = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(json["key"]["module_prefix"], "System");
+        assert_eq!(json["key"]["ty"]["kind"], "Map");
+        assert_eq!(
+            json["key"]["ty"]["key"],
+            "0xbe5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f"
+        );
+        assert_eq!(json["value"]["nonce"], 1);
+        assert_eq!(json["value"]["data"]["free"], "100");
+        assert_eq!(json["value"]["data"]["fee_frozen"], "400");
+    }
+
+    #[test]
+    fn decode_well_known_storage_should_work() {
+        let table: StorageMetadataLookupTable = test_metadata().into();
+        let decoder = ValueDecoder::default();
+
+        // hex(":heappages")
+        let decoded = table
+            .decode_storage(&decoder, "3a686561707061676573".into(), Some(8u64.encode()))
+            .unwrap();
+        assert_eq!(decoded.key, DecodedKey::WellKnown(WellKnownKey::HeapPages));
+        assert_eq!(decoded.value, Some(Value::U64(8)));
+
+        assert!(table.parse_storage_key("3a636f6465".into()).is_none());
+    }
+
+    #[test]
+    fn parse_non_hex_storage_key_should_fail() {
+        let table: StorageMetadataLookupTable = test_metadata().into();
+
+        // The multi-byte character straddles the end of the prefix.
+        let key = format!("{}\u{e9}", "a".repeat(63));
+        assert!(table.parse_storage_key(key.clone()).is_none());
+        assert!(table.group_by_prefix(vec![(key, None)]).is_empty());
+
+        // System Account, the multi-byte character straddles the end of the hashed key.
+        let key = format!(
+            "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9{}\u{e9}",
+            "a".repeat(31)
+        );
+        assert!(table.parse_storage_key(key).is_none());
+    }
+
+    #[cfg(feature = "runtime")]
     #[test]
     fn test_decode_storage_value() {
         use codec::Encode;
         use frame_system::AccountInfo;
         use pallet_balances::AccountData;
-        use polkadot_primitives::v1::{AccountIndex, Balance};
         use std::collections::HashMap;
 
         // Firstly, we need to build Storage Value decode function table by hand.
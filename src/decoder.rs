@@ -0,0 +1,776 @@
+use std::{collections::HashMap, str::FromStr};
+
+use codec::{Compact, Decode, Input};
+
+use crate::value::Value;
+
+/// Dynamic decoding error.
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    /// Failed to parse the type name from metadata.
+    #[error("Invalid type name {0}: {1}")]
+    InvalidTypeName(String, &'static str),
+    /// Type is not in the type registry.
+    #[error("Unknown type {0}")]
+    UnknownType(String),
+    /// Variant index is out of range.
+    #[error("Invalid variant index {1} for {0}")]
+    InvalidVariant(String, u8),
+    /// Input is not fully consumed.
+    #[error("{0} bytes left after decoding")]
+    TrailingBytes(usize),
+    /// Decoded value does not have the expected shape of the type.
+    #[error("Unexpected value of {0}: {1}")]
+    UnexpectedValue(String, Value),
+    /// Codec error.
+    #[error("Codec error: {0}")]
+    Codec(#[from] codec::Error),
+}
+
+/// Type name in metadata, e.g., `Vec<(T::AccountId, BalanceOf<T>)>`.
+///
+/// The qualified paths like `T::AccountId` and `<T as Trait>::Call` are reduced to
+/// the last segment, i.e., `AccountId` and `Call`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TypeName {
+    Path { name: String, params: Vec<TypeName> },
+    Tuple(Vec<TypeName>),
+    Array(Box<TypeName>, usize),
+}
+
+impl TypeName {
+    pub(crate) fn is_u8(&self) -> bool {
+        matches!(self, TypeName::Path { name, params } if name == "u8" && params.is_empty())
+    }
+}
+
+impl FromStr for TypeName {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = TypeNameParser {
+            source: s,
+            chars: s.chars().filter(|c| !c.is_whitespace()).collect(),
+            pos: 0,
+        };
+        let ty = parser.parse()?;
+        if parser.pos != parser.chars.len() {
+            return Err(parser.error("Unexpected trailing characters"));
+        }
+        Ok(ty)
+    }
+}
+
+struct TypeNameParser<'a> {
+    source: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'a> TypeNameParser<'a> {
+    fn error(&self, reason: &'static str) -> DecodeError {
+        DecodeError::InvalidTypeName(self.source.to_string(), reason)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char, reason: &'static str) -> Result<(), DecodeError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(reason))
+        }
+    }
+
+    fn parse(&mut self) -> Result<TypeName, DecodeError> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let items = self.parse_list(')')?;
+                Ok(TypeName::Tuple(items))
+            }
+            Some('[') => {
+                self.pos += 1;
+                let inner = self.parse()?;
+                self.expect(';', "Expected `;` in array")?;
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                let len = self.chars[start..self.pos]
+                    .iter()
+                    .collect::<String>()
+                    .parse()
+                    .map_err(|_| self.error("Invalid array length"))?;
+                self.expect(']', "Expected closing `]` for array")?;
+                Ok(TypeName::Array(Box::new(inner), len))
+            }
+            _ => self.parse_path(),
+        }
+    }
+
+    /// Parses the comma separated types until `close`.
+    fn parse_list(&mut self, close: char) -> Result<Vec<TypeName>, DecodeError> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(self.parse()?);
+            if !self.eat(',') {
+                self.expect(close, "Expected closing bracket")?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn parse_path(&mut self) -> Result<TypeName, DecodeError> {
+        loop {
+            // Skip the qualified self type, e.g., `<T as Trait<I>>::`.
+            if self.eat('<') {
+                let mut depth = 1;
+                while depth > 0 {
+                    match self.peek() {
+                        Some('<') => depth += 1,
+                        Some('>') => depth -= 1,
+                        Some(_) => {}
+                        None => return Err(self.error("Unclosed qualified path")),
+                    }
+                    self.pos += 1;
+                }
+                self.expect(':', "Expected `::` after qualified path")?;
+                self.expect(':', "Expected `::` after qualified path")?;
+            }
+
+            let start = self.pos;
+            while matches!(self.peek(), Some(c) if c.is_alphanumeric() || c == '_') {
+                self.pos += 1;
+            }
+            if start == self.pos {
+                return Err(self.error("Expected identifier"));
+            }
+            let name: String = self.chars[start..self.pos].iter().collect();
+
+            if self.eat(':') {
+                self.expect(':', "Expected `::` in path")?;
+                continue;
+            }
+
+            let params = if self.eat('<') {
+                self.parse_list('>')?
+            } else {
+                Vec::new()
+            };
+
+            return Ok(TypeName::Path { name, params });
+        }
+    }
+}
+
+/// Definition of a type which can not be decoded structurally from its name.
+#[derive(Clone, Debug)]
+pub enum TypeDef {
+    /// Alias of another type, e.g., `Balance` -> `u128`.
+    Alias(String),
+    /// Struct of `(field_name, field_type)`.
+    Composite(Vec<(String, String)>),
+    /// Enum of `(variant_name, field_types)`, the variant index is the position.
+    Variant(Vec<(String, Vec<String>)>),
+}
+
+/// Decodes the storage value given the type name in metadata, without the concrete Rust type.
+///
+/// The generic types `Vec`, `Option`, `Compact`, `Box`, `BTreeMap`, tuples and arrays are
+/// decoded structurally, the others are looked up in the type registry.
+#[derive(Clone, Debug)]
+pub struct ValueDecoder {
+    types: HashMap<String, TypeDef>,
+}
+
+impl Default for ValueDecoder {
+    fn default() -> Self {
+        let mut decoder = Self {
+            types: HashMap::new(),
+        };
+        decoder.register_default_types();
+        decoder
+    }
+}
+
+fn alias(ty: &str) -> TypeDef {
+    TypeDef::Alias(ty.into())
+}
+
+fn composite(fields: &[(&str, &str)]) -> TypeDef {
+    TypeDef::Composite(
+        fields
+            .iter()
+            .map(|(name, ty)| (String::from(*name), String::from(*ty)))
+            .collect(),
+    )
+}
+
+fn variant(variants: &[(&str, &[&str])]) -> TypeDef {
+    TypeDef::Variant(
+        variants
+            .iter()
+            .map(|(name, fields)| {
+                (
+                    String::from(*name),
+                    fields.iter().map(|ty| String::from(*ty)).collect(),
+                )
+            })
+            .collect(),
+    )
+}
+
+impl ValueDecoder {
+    /// Registers a type definition, overrides the existing one if any.
+    pub fn register<S: Into<String>>(&mut self, name: S, def: TypeDef) {
+        self.types.insert(name.into(), def);
+    }
+
+    /// Returns the definition of the type `name` in the type registry.
+    pub fn type_def(&self, name: &str) -> Option<&TypeDef> {
+        self.types.get(name)
+    }
+
+    /// Types of the Polkadot runtime.
+    ///
+    /// TODO: cover all the value types in metadata.
+    fn register_default_types(&mut self) {
+        let aliases = [
+            ("AccountId", "[u8; 32]"),
+            ("AccountIndex", "u32"),
+            ("AuthIndex", "u32"),
+            ("AuthorityList", "Vec<(AuthorityId, AuthorityWeight)>"),
+            ("AuthorityWeight", "u64"),
+            ("AuthorityId", "[u8; 32]"),
+            ("BabeAuthorityWeight", "u64"),
+            ("Balance", "u128"),
+            ("BalanceOf", "Balance"),
+            ("BlockNumber", "u32"),
+            ("CallHash", "[u8; 32]"),
+            ("EraIndex", "u32"),
+            ("EthereumAddress", "[u8; 20]"),
+            ("EventIndex", "u32"),
+            ("H256", "[u8; 32]"),
+            ("Hash", "H256"),
+            ("Index", "u32"),
+            ("LookupSource", "Address"),
+            ("MemberCount", "u32"),
+            ("MaybeRandomness", "Option<Randomness>"),
+            ("Moment", "u64"),
+            ("Multiplier", "u128"),
+            ("Perbill", "u32"),
+            ("Percent", "u8"),
+            ("Permill", "u32"),
+            ("PropIndex", "u32"),
+            ("ProposalIndex", "u32"),
+            ("Randomness", "[u8; 32]"),
+            ("ReferendumIndex", "u32"),
+            ("RefCount", "u8"),
+            ("SessionIndex", "u32"),
+            ("SetId", "u64"),
+            ("Signature", "[u8; 64]"),
+            // `<T::Lookup as StaticLookup>::Source` of `pallet_indices`.
+            ("Source", "Address"),
+            ("ValidatorId", "AccountId"),
+            ("Weight", "u64"),
+        ];
+        for (name, ty) in aliases.iter() {
+            self.register(*name, alias(ty));
+        }
+
+        self.register(
+            "AccountInfo",
+            composite(&[
+                ("nonce", "Index"),
+                ("refcount", "RefCount"),
+                ("data", "AccountData"),
+            ]),
+        );
+        self.register(
+            "AccountData",
+            composite(&[
+                ("free", "Balance"),
+                ("reserved", "Balance"),
+                ("misc_frozen", "Balance"),
+                ("fee_frozen", "Balance"),
+            ]),
+        );
+        self.register(
+            "BalanceLock",
+            composite(&[
+                ("id", "[u8; 8]"),
+                ("amount", "Balance"),
+                ("reasons", "Reasons"),
+            ]),
+        );
+        self.register(
+            "Reasons",
+            variant(&[("Fee", &[]), ("Misc", &[]), ("All", &[])]),
+        );
+        self.register(
+            "ActiveEraInfo",
+            composite(&[("index", "EraIndex"), ("start", "Option<Moment>")]),
+        );
+        self.register(
+            "Exposure",
+            composite(&[
+                ("total", "Compact<Balance>"),
+                ("own", "Compact<Balance>"),
+                ("others", "Vec<IndividualExposure>"),
+            ]),
+        );
+        self.register(
+            "IndividualExposure",
+            composite(&[("who", "AccountId"), ("value", "Compact<Balance>")]),
+        );
+        self.register(
+            "StakingLedger",
+            composite(&[
+                ("stash", "AccountId"),
+                ("total", "Compact<Balance>"),
+                ("active", "Compact<Balance>"),
+                ("unlocking", "Vec<UnlockChunk>"),
+                ("claimed_rewards", "Vec<EraIndex>"),
+            ]),
+        );
+        self.register(
+            "UnlockChunk",
+            composite(&[("value", "Compact<Balance>"), ("era", "Compact<EraIndex>")]),
+        );
+        self.register(
+            "ValidatorPrefs",
+            composite(&[("commission", "Compact<Perbill>")]),
+        );
+        self.register(
+            "Nominations",
+            composite(&[
+                ("targets", "Vec<AccountId>"),
+                ("submitted_in", "EraIndex"),
+                ("suppressed", "bool"),
+            ]),
+        );
+        self.register(
+            "RewardDestination",
+            variant(&[("Staked", &[]), ("Stash", &[]), ("Controller", &[])]),
+        );
+        self.register(
+            "Forcing",
+            variant(&[
+                ("NotForcing", &[]),
+                ("ForceNew", &[]),
+                ("ForceNone", &[]),
+                ("ForceAlways", &[]),
+            ]),
+        );
+        self.register(
+            "LastRuntimeUpgradeInfo",
+            composite(&[("spec_version", "Compact<u32>"), ("spec_name", "String")]),
+        );
+        self.register(
+            "Phase",
+            variant(&[
+                ("ApplyExtrinsic", &["u32"]),
+                ("Finalization", &[]),
+                ("Initialization", &[]),
+            ]),
+        );
+        self.register(
+            "DispatchInfo",
+            composite(&[
+                ("weight", "Weight"),
+                ("class", "DispatchClass"),
+                ("pays_fee", "Pays"),
+            ]),
+        );
+        self.register(
+            "DispatchClass",
+            variant(&[("Normal", &[]), ("Operational", &[]), ("Mandatory", &[])]),
+        );
+        self.register("Pays", variant(&[("Yes", &[]), ("No", &[])]));
+        // The message of `Module` is skipped in the encoding.
+        self.register(
+            "DispatchError",
+            variant(&[
+                ("Other", &[]),
+                ("CannotLookup", &[]),
+                ("BadOrigin", &[]),
+                ("Module", &["u8", "u8"]),
+            ]),
+        );
+        self.register(
+            "DispatchResult",
+            variant(&[("Ok", &[]), ("Err", &["DispatchError"])]),
+        );
+        self.register(
+            "BalanceStatus",
+            variant(&[("Free", &[]), ("Reserved", &[])]),
+        );
+        self.register(
+            "Timepoint",
+            composite(&[("height", "BlockNumber"), ("index", "u32")]),
+        );
+        self.register(
+            "MultiSignature",
+            variant(&[
+                ("Ed25519", &["[u8; 64]"]),
+                ("Sr25519", &["[u8; 64]"]),
+                ("Ecdsa", &["[u8; 65]"]),
+            ]),
+        );
+        self.register(
+            "ChangesTrieConfiguration",
+            composite(&[("digest_interval", "u32"), ("digest_levels", "u32")]),
+        );
+        self.register(
+            "VestingInfo",
+            composite(&[
+                ("locked", "Balance"),
+                ("per_block", "Balance"),
+                ("starting_block", "BlockNumber"),
+            ]),
+        );
+    }
+
+    /// Decodes a value of type `ty` from `input`.
+    pub fn decode(&self, ty: &str, input: &mut &[u8]) -> Result<Value, DecodeError> {
+        let ty: TypeName = ty.parse()?;
+        self.decode_type(&ty, input)
+    }
+
+    /// Decodes a value of type `ty`, `encoded` must be consumed completely.
+    pub fn decode_all(&self, ty: &str, encoded: &[u8]) -> Result<Value, DecodeError> {
+        let mut input = encoded;
+        let value = self.decode(ty, &mut input)?;
+        if !input.is_empty() {
+            return Err(DecodeError::TrailingBytes(input.len()));
+        }
+        Ok(value)
+    }
+
+    fn decode_type(&self, ty: &TypeName, input: &mut &[u8]) -> Result<Value, DecodeError> {
+        match ty {
+            TypeName::Tuple(items) => Ok(Value::Sequence(
+                items
+                    .iter()
+                    .map(|item| self.decode_type(item, input))
+                    .collect::<Result<_, _>>()?,
+            )),
+            TypeName::Array(inner, len) => {
+                if inner.is_u8() {
+                    let mut bytes = vec![0u8; *len];
+                    input.read(&mut bytes)?;
+                    Ok(Value::Bytes(bytes))
+                } else {
+                    Ok(Value::Sequence(
+                        (0..*len)
+                            .map(|_| self.decode_type(inner, input))
+                            .collect::<Result<_, _>>()?,
+                    ))
+                }
+            }
+            TypeName::Path { name, params } => self.decode_path(name, params, input),
+        }
+    }
+
+    fn decode_path(
+        &self,
+        name: &str,
+        params: &[TypeName],
+        input: &mut &[u8],
+    ) -> Result<Value, DecodeError> {
+        let value: Value = match (name, params) {
+            ("bool", []) => bool::decode(input)?.into(),
+            ("u8", []) => u8::decode(input)?.into(),
+            ("u16", []) => u16::decode(input)?.into(),
+            ("u32", []) => u32::decode(input)?.into(),
+            ("u64", []) => u64::decode(input)?.into(),
+            ("u128", []) => u128::decode(input)?.into(),
+            ("String", []) | ("Text", []) => String::decode(input)?.into(),
+            ("Box", [inner]) => self.decode_type(inner, input)?,
+            ("Compact", [inner]) => self.decode_compact(inner, input)?,
+            ("Vec", [inner]) if inner.is_u8() => Vec::<u8>::decode(input)?.into(),
+            ("Vec", [inner]) => {
+                let len = Compact::<u32>::decode(input)?.0;
+                Value::Sequence(
+                    (0..len)
+                        .map(|_| self.decode_type(inner, input))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            ("BTreeMap", [key, value]) => {
+                let len = Compact::<u32>::decode(input)?.0;
+                Value::Sequence(
+                    (0..len)
+                        .map(|_| {
+                            Ok(Value::composite(vec![
+                                ("key", self.decode_type(key, input)?),
+                                ("value", self.decode_type(value, input)?),
+                            ]))
+                        })
+                        .collect::<Result<_, DecodeError>>()?,
+                )
+            }
+            ("Address", []) => self.decode_address(input)?,
+            ("Era", []) => Self::decode_era(input)?,
+            ("Option", [inner]) => match u8::decode(input)? {
+                0 => Value::Variant("None".into(), Vec::new()),
+                1 => Value::Variant("Some".into(), vec![self.decode_type(inner, input)?]),
+                i => return Err(DecodeError::InvalidVariant(name.into(), i)),
+            },
+            _ => match self.types.get(name) {
+                Some(def) => self.decode_def(name, def, input)?,
+                None => return Err(DecodeError::UnknownType(name.into())),
+            },
+        };
+        Ok(value)
+    }
+
+    fn decode_def(
+        &self,
+        name: &str,
+        def: &TypeDef,
+        input: &mut &[u8],
+    ) -> Result<Value, DecodeError> {
+        match def {
+            TypeDef::Alias(ty) => self.decode(ty, input),
+            TypeDef::Composite(fields) => {
+                let mut values = Vec::with_capacity(fields.len());
+                for (field, ty) in fields {
+                    values.push((field.clone(), self.decode(ty, input)?));
+                }
+                Ok(Value::Composite(values))
+            }
+            TypeDef::Variant(variants) => {
+                let index = u8::decode(input)?;
+                let (variant, fields) = variants
+                    .get(index as usize)
+                    .ok_or_else(|| DecodeError::InvalidVariant(name.into(), index))?;
+                let fields = fields
+                    .iter()
+                    .map(|ty| self.decode(ty, input))
+                    .collect::<Result<_, _>>()?;
+                Ok(Value::Variant(variant.clone(), fields))
+            }
+        }
+    }
+
+    /// Resolves the aliases of `ty` to a primitive type name.
+    pub(crate) fn resolve_primitive(&self, ty: &TypeName) -> Result<String, DecodeError> {
+        if let TypeName::Path { name, .. } = ty {
+            match self.types.get(name) {
+                Some(TypeDef::Alias(alias)) => return self.resolve_primitive(&alias.parse()?),
+                None => return Ok(name.clone()),
+                Some(_) => {}
+            }
+        }
+        Err(DecodeError::UnknownType(format!("Compact<{:?}>", ty)))
+    }
+
+    /// Decodes `pallet_indices::address::Address<AccountId, AccountIndex>`, the account id is
+    /// prefixed with `0xff`, the account index is either the byte itself if at most `0xef`,
+    /// or prefixed with `0xfc`, `0xfd` and `0xfe` for u16, u32 and u64.
+    fn decode_address(&self, input: &mut &[u8]) -> Result<Value, DecodeError> {
+        let index: Value = match u8::decode(input)? {
+            0xff => {
+                return Ok(Value::Variant(
+                    "Id".into(),
+                    vec![self.decode("AccountId", input)?],
+                ))
+            }
+            0xfe => u64::decode(input)?.into(),
+            0xfd => u32::decode(input)?.into(),
+            0xfc => u16::decode(input)?.into(),
+            i @ 0x00..=0xef => i.into(),
+            i => return Err(DecodeError::InvalidVariant("Address".into(), i)),
+        };
+        Ok(Value::Variant("Index".into(), vec![index]))
+    }
+
+    /// Decodes `sp_runtime::generic::Era`, the mortal era is encoded in two bytes with the
+    /// period and the quantized phase, the period must be at least 4 and above the phase.
+    fn decode_era(input: &mut &[u8]) -> Result<Value, DecodeError> {
+        let first = u8::decode(input)?;
+        if first == 0 {
+            return Ok(Value::Variant("Immortal".into(), Vec::new()));
+        }
+        let encoded = first as u64 + ((u8::decode(input)? as u64) << 8);
+        let period = 2 << (encoded % (1 << 4));
+        let quantize_factor = (period >> 12).max(1);
+        let phase = (encoded >> 4) * quantize_factor;
+        if period < 4 || phase >= period {
+            return Err(DecodeError::InvalidVariant("Era".into(), first));
+        }
+        Ok(Value::Variant(
+            "Mortal".into(),
+            vec![Value::composite(vec![
+                ("period", period.into()),
+                ("phase", phase.into()),
+            ])],
+        ))
+    }
+
+    fn decode_compact(&self, inner: &TypeName, input: &mut &[u8]) -> Result<Value, DecodeError> {
+        let value: Value = match self.resolve_primitive(inner)?.as_str() {
+            "u8" => Compact::<u8>::decode(input)?.0.into(),
+            "u16" => Compact::<u16>::decode(input)?.0.into(),
+            "u32" => Compact::<u32>::decode(input)?.0.into(),
+            "u64" => Compact::<u64>::decode(input)?.0.into(),
+            "u128" => Compact::<u128>::decode(input)?.0.into(),
+            other => return Err(DecodeError::UnknownType(format!("Compact<{}>", other))),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::Encode;
+
+    fn path(name: &str, params: Vec<TypeName>) -> TypeName {
+        TypeName::Path {
+            name: name.into(),
+            params,
+        }
+    }
+
+    #[test]
+    fn parse_type_name_should_work() {
+        assert_eq!(
+            "Vec<(T::AccountId, BalanceOf<T>)>"
+                .parse::<TypeName>()
+                .unwrap(),
+            path(
+                "Vec",
+                vec![TypeName::Tuple(vec![
+                    path("AccountId", vec![]),
+                    path("BalanceOf", vec![path("T", vec![])]),
+                ])]
+            )
+        );
+        assert_eq!(
+            "<T as Trait<I>>::Proposal".parse::<TypeName>().unwrap(),
+            path("Proposal", vec![])
+        );
+        assert_eq!(
+            "[u8; 32]".parse::<TypeName>().unwrap(),
+            TypeName::Array(Box::new(path("u8", vec![])), 32)
+        );
+        assert!("Vec<u8".parse::<TypeName>().is_err());
+    }
+
+    #[test]
+    fn decode_account_info_should_work() {
+        let decoder = ValueDecoder::default();
+        let encoded = hex::decode("010000000864000000000000000000000000000000c80000000000000000000000000000002c01000000000000000000000000000090010000000000000000000000000000").unwrap();
+        let expected = Value::composite(vec![
+            ("nonce", Value::U32(1)),
+            ("refcount", Value::U8(8)),
+            (
+                "data",
+                Value::composite(vec![
+                    ("free", Value::U128(100)),
+                    ("reserved", Value::U128(200)),
+                    ("misc_frozen", Value::U128(300)),
+                    ("fee_frozen", Value::U128(400)),
+                ]),
+            ),
+        ]);
+        assert_eq!(
+            decoder
+                .decode_all("AccountInfo<T::Index, T::AccountData>", &encoded)
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn decode_generic_types_should_work() {
+        let decoder = ValueDecoder::default();
+        let encoded = (vec![(1u32, Compact(2u128))], Some(true)).encode();
+        assert_eq!(
+            decoder
+                .decode_all(
+                    "(Vec<(EraIndex, Compact<BalanceOf<T>>)>, Option<bool>)",
+                    &encoded
+                )
+                .unwrap(),
+            Value::Sequence(vec![
+                Value::Sequence(vec![Value::Sequence(vec![Value::U32(1), Value::U128(2)])]),
+                Value::Variant("Some".into(), vec![Value::Bool(true)]),
+            ])
+        );
+        assert!(matches!(
+            decoder.decode_all("u32", &[0u8; 5]),
+            Err(DecodeError::TrailingBytes(1))
+        ));
+    }
+
+    #[test]
+    fn decode_address_should_work() {
+        let decoder = ValueDecoder::default();
+        let ty = "<T::Lookup as StaticLookup>::Source";
+        let mut id = vec![0xff];
+        id.extend(&[1u8; 32]);
+        assert_eq!(
+            decoder.decode_all(ty, &id).unwrap(),
+            Value::Variant("Id".into(), vec![Value::Bytes(vec![1; 32])])
+        );
+        assert_eq!(
+            decoder.decode_all(ty, &[3]).unwrap(),
+            Value::Variant("Index".into(), vec![Value::U8(3)])
+        );
+        assert_eq!(
+            decoder.decode_all(ty, &[0xef]).unwrap(),
+            Value::Variant("Index".into(), vec![Value::U8(0xef)])
+        );
+        assert_eq!(
+            decoder.decode_all(ty, &[0xfd, 1, 0, 0, 0]).unwrap(),
+            Value::Variant("Index".into(), vec![Value::U32(1)])
+        );
+        assert!(matches!(
+            decoder.decode_all(ty, &[0xf0]),
+            Err(DecodeError::InvalidVariant(..))
+        ));
+    }
+
+    #[test]
+    fn decode_era_should_work() {
+        let decoder = ValueDecoder::default();
+        assert_eq!(
+            decoder.decode_all("Era", &[0]).unwrap(),
+            Value::Variant("Immortal".into(), Vec::new())
+        );
+        // Era::mortal(64, 42), i.e., period 64 and phase 42.
+        assert_eq!(
+            decoder.decode_all("Era", &[0xa5, 0x02]).unwrap(),
+            Value::Variant(
+                "Mortal".into(),
+                vec![Value::composite(vec![
+                    ("period", Value::U64(64)),
+                    ("phase", Value::U64(42)),
+                ])]
+            )
+        );
+        // Period 2, and period 4 with phase 4.
+        for invalid in &[[0x10, 0x00], [0x41, 0x00]] {
+            assert!(matches!(
+                decoder.decode_all("Era", invalid),
+                Err(DecodeError::InvalidVariant(..))
+            ));
+        }
+    }
+}
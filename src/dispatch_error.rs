@@ -0,0 +1,147 @@
+//! Resolves `DispatchError::Module { index, error }` into the pallet error in metadata.
+//!
+//! `index` is the position of the pallet among all the pallets and `error` is the position of
+//! the error within the pallet, which is exactly how the nested enums are encoded. So
+//! [`register_error_types`] registers the errors of each pallet as an enum in the
+//! [`ValueDecoder`], and `DispatchError` is decoded as e.g. `Module(Balances(InsufficientBalance))`.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::decoder::{TypeDef, ValueDecoder};
+use crate::metadata::{Metadata, MetadataError};
+use crate::value::Value;
+
+/// Error of a pallet in metadata.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ModuleError {
+    pub pallet: String,
+    pub error: String,
+    pub documentation: Vec<String>,
+}
+
+impl fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}::{}", self.pallet, self.error)
+    }
+}
+
+/// Name of the type of the errors of `pallet`.
+fn pallet_error_type(pallet: &str) -> String {
+    format!("{}Error", pallet)
+}
+
+/// Registers `DispatchError` whose `Module` variant is resolved to the pallet error, plus the
+/// error enum of each pallet, in `decoder`.
+pub fn register_error_types(decoder: &mut ValueDecoder, metadata: &Metadata) {
+    let mut modules = metadata.modules_with_errors.values().collect::<Vec<_>>();
+    modules.sort_by_key(|module| module.index());
+
+    let mut pallets = Vec::with_capacity(modules.len());
+    for module in modules {
+        let errors = module
+            .errors()
+            .map(|error| (error.name.clone(), Vec::new()))
+            .collect();
+        let error_ty = pallet_error_type(module.name());
+        decoder.register(error_ty.clone(), TypeDef::Variant(errors));
+        pallets.push((module.name().to_string(), vec![error_ty]));
+    }
+    decoder.register("ModuleError", TypeDef::Variant(pallets));
+
+    decoder.register(
+        "DispatchError",
+        TypeDef::Variant(vec![
+            ("Other".into(), Vec::new()),
+            ("CannotLookup".into(), Vec::new()),
+            ("BadOrigin".into(), Vec::new()),
+            ("Module".into(), vec!["ModuleError".into()]),
+        ]),
+    );
+}
+
+impl ModuleError {
+    /// Returns the error `error` of the pallet at `index` of all the pallets.
+    pub fn resolve(metadata: &Metadata, index: u8, error: u8) -> Result<Self, MetadataError> {
+        let module = metadata.module_with_errors(index)?;
+        let error = module.error(error)?;
+        Ok(Self {
+            pallet: module.name().into(),
+            error: error.name.clone(),
+            documentation: error.documentation().to_vec(),
+        })
+    }
+
+    /// Returns the pallet error of the decoded `DispatchError`, `None` if it is not
+    /// `DispatchError::Module`.
+    ///
+    /// Both the `DispatchError` decoded with and without the types of [`register_error_types`]
+    /// are accepted.
+    pub fn from_value(metadata: &Metadata, value: &Value) -> Option<Self> {
+        match value {
+            Value::Variant(variant, fields) if variant == "Module" => match fields.as_slice() {
+                [Value::U8(index), Value::U8(error)] => {
+                    Self::resolve(metadata, *index, *error).ok()
+                }
+                [Value::Variant(pallet, errors)] => match errors.as_slice() {
+                    [Value::Variant(error, _)] => {
+                        let module = metadata.modules_with_errors.get(pallet)?;
+                        let error = module.error_by_name(error).ok()?;
+                        Some(Self {
+                            pallet: pallet.clone(),
+                            error: error.name.clone(),
+                            documentation: error.documentation().to_vec(),
+                        })
+                    }
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::test_metadata;
+    use codec::Encode;
+
+    #[test]
+    fn resolve_module_error_should_work() {
+        let metadata = test_metadata();
+        let module = &metadata.modules_with_errors["Balances"];
+        let index = module.error_by_name("InsufficientBalance").unwrap().index();
+        let encoded = (3u8, module.index(), index).encode();
+
+        let plain = ValueDecoder::default()
+            .decode_all("DispatchError", &encoded)
+            .unwrap();
+        let error = ModuleError::from_value(&metadata, &plain).unwrap();
+        assert_eq!(error.to_string(), "Balances::InsufficientBalance");
+        assert!(!error.documentation.is_empty());
+
+        let mut decoder = ValueDecoder::default();
+        register_error_types(&mut decoder, &metadata);
+        let resolved = decoder.decode_all("DispatchError", &encoded).unwrap();
+        assert_eq!(
+            resolved,
+            Value::Variant(
+                "Module".into(),
+                vec![Value::Variant(
+                    "Balances".into(),
+                    vec![Value::Variant("InsufficientBalance".into(), Vec::new())]
+                )]
+            )
+        );
+        assert_eq!(ModuleError::from_value(&metadata, &resolved), Some(error));
+
+        assert_eq!(
+            ModuleError::from_value(&metadata, &Value::Variant("BadOrigin".into(), Vec::new())),
+            None
+        );
+        assert!(ModuleError::resolve(&metadata, 255, 0).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+//! Encodes the JSON values by the type names in metadata, the reverse of [`ValueDecoder`].
+//!
+//! The accepted JSON is the same as the JSON output of the decoded [`Value`], so a decoded value
+//! can be edited and encoded again. Besides, the integers can be given as decimal strings, the
+//! bytes as `0x` prefixed hex strings, the account ids as SS58 addresses and `Option<T>` as
+//! `null` or the inner value.
+//!
+//! [`Value`]: crate::value::Value
+
+use std::convert::TryFrom;
+
+use codec::{Compact, Encode};
+use serde_json::Value as JsonValue;
+use sp_core::crypto::{AccountId32, Ss58Codec};
+
+use crate::decoder::{DecodeError, TypeDef, TypeName, ValueDecoder};
+
+/// Dynamic encoding error.
+#[derive(Debug, thiserror::Error)]
+pub enum EncodeError {
+    /// Invalid type name or unknown type.
+    #[error(transparent)]
+    Type(#[from] DecodeError),
+    /// JSON value does not match the type.
+    #[error("Invalid value {1} for {0}")]
+    InvalidValue(String, JsonValue),
+    /// Field of a struct or argument of a call is missing.
+    #[error("Missing field {1} of {0}")]
+    MissingField(String, String),
+    /// Variant is not in the enum.
+    #[error("Unknown variant {1} of {0}")]
+    UnknownVariant(String, String),
+}
+
+fn invalid(ty: &str, value: &JsonValue) -> EncodeError {
+    EncodeError::InvalidValue(ty.into(), value.clone())
+}
+
+/// Parses an unsigned integer given as a JSON number or a decimal string.
+fn uint<T: TryFrom<u128>>(ty: &str, value: &JsonValue) -> Result<T, EncodeError> {
+    let n = match value {
+        JsonValue::Number(n) => n.as_u64().map(u128::from),
+        JsonValue::String(s) => s.parse().ok(),
+        _ => None,
+    };
+    n.and_then(|n| T::try_from(n).ok())
+        .ok_or_else(|| invalid(ty, value))
+}
+
+/// Parses the bytes given as a `0x` prefixed hex string or an SS58 address.
+fn bytes(ty: &str, value: &JsonValue) -> Result<Vec<u8>, EncodeError> {
+    let s = value.as_str().ok_or_else(|| invalid(ty, value))?;
+    match s.strip_prefix("0x") {
+        Some(hex) => hex::decode(hex).map_err(|_| invalid(ty, value)),
+        None => AccountId32::from_ss58check(s)
+            .map(|account| AsRef::<[u8]>::as_ref(&account).to_vec())
+            .map_err(|_| invalid(ty, value)),
+    }
+}
+
+/// Returns the elements of a JSON array of `len` elements.
+fn elements<'v>(
+    ty: &str,
+    value: &'v JsonValue,
+    len: Option<usize>,
+) -> Result<&'v [JsonValue], EncodeError> {
+    match value.as_array() {
+        Some(items) if len.is_none() || len == Some(items.len()) => Ok(items),
+        _ => Err(invalid(ty, value)),
+    }
+}
+
+/// Encodes the JSON values using the type registry of a [`ValueDecoder`].
+pub struct ValueEncoder<'a> {
+    decoder: &'a ValueDecoder,
+}
+
+impl<'a> ValueEncoder<'a> {
+    pub fn new(decoder: &'a ValueDecoder) -> Self {
+        Self { decoder }
+    }
+
+    /// Encodes `value` as type `ty`.
+    pub fn encode(&self, ty: &str, value: &JsonValue) -> Result<Vec<u8>, EncodeError> {
+        let mut out = Vec::new();
+        self.encode_to(ty, value, &mut out)?;
+        Ok(out)
+    }
+
+    /// Encodes `value` as type `ty`, appending to `out`.
+    pub fn encode_to(
+        &self,
+        ty: &str,
+        value: &JsonValue,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        let ty: TypeName = ty.parse()?;
+        self.encode_type(&ty, value, out)
+    }
+
+    fn encode_type(
+        &self,
+        ty: &TypeName,
+        value: &JsonValue,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        match ty {
+            TypeName::Tuple(items) => {
+                let values = elements("tuple", value, Some(items.len()))?;
+                for (item, value) in items.iter().zip(values) {
+                    self.encode_type(item, value, out)?;
+                }
+            }
+            TypeName::Array(inner, len) => {
+                if inner.is_u8() {
+                    let bytes = bytes("array", value)?;
+                    if bytes.len() != *len {
+                        return Err(invalid("array", value));
+                    }
+                    out.extend(bytes);
+                } else {
+                    for value in elements("array", value, Some(*len))? {
+                        self.encode_type(inner, value, out)?;
+                    }
+                }
+            }
+            TypeName::Path { name, params } => self.encode_path(name, params, value, out)?,
+        }
+        Ok(())
+    }
+
+    fn encode_path(
+        &self,
+        name: &str,
+        params: &[TypeName],
+        value: &JsonValue,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        match (name, params) {
+            ("bool", []) => value
+                .as_bool()
+                .ok_or_else(|| invalid(name, value))?
+                .encode_to(out),
+            ("u8", []) => uint::<u8>(name, value)?.encode_to(out),
+            ("u16", []) => uint::<u16>(name, value)?.encode_to(out),
+            ("u32", []) => uint::<u32>(name, value)?.encode_to(out),
+            ("u64", []) => uint::<u64>(name, value)?.encode_to(out),
+            ("u128", []) => uint::<u128>(name, value)?.encode_to(out),
+            ("String", []) | ("Text", []) => value
+                .as_str()
+                .ok_or_else(|| invalid(name, value))?
+                .encode_to(out),
+            ("Box", [inner]) => self.encode_type(inner, value, out)?,
+            ("Compact", [inner]) => self.encode_compact(inner, value, out)?,
+            ("Vec", [inner]) if inner.is_u8() => bytes(name, value)?.encode_to(out),
+            ("Vec", [inner]) => {
+                let values = elements(name, value, None)?;
+                Compact(values.len() as u32).encode_to(out);
+                for value in values {
+                    self.encode_type(inner, value, out)?;
+                }
+            }
+            ("BTreeMap", [key_ty, value_ty]) => {
+                let entries = elements(name, value, None)?;
+                Compact(entries.len() as u32).encode_to(out);
+                for entry in entries {
+                    let field = |field: &str| {
+                        entry
+                            .get(field)
+                            .ok_or_else(|| EncodeError::MissingField(name.into(), field.into()))
+                    };
+                    self.encode_type(key_ty, field("key")?, out)?;
+                    self.encode_type(value_ty, field("value")?, out)?;
+                }
+            }
+            ("Address", []) => self.encode_address(value, out)?,
+            ("Era", []) => Self::encode_era(value, out)?,
+            ("Option", [inner]) => match value {
+                JsonValue::Null => out.push(0),
+                JsonValue::String(s) if s == "None" => out.push(0),
+                JsonValue::Object(map) if map.len() == 1 && map.contains_key("Some") => {
+                    out.push(1);
+                    self.encode_type(inner, &map["Some"], out)?;
+                }
+                _ => {
+                    out.push(1);
+                    self.encode_type(inner, value, out)?;
+                }
+            },
+            _ => match self.decoder.type_def(name) {
+                Some(def) => self.encode_def(name, def, value, out)?,
+                None => return Err(DecodeError::UnknownType(name.into()).into()),
+            },
+        }
+        Ok(())
+    }
+
+    fn encode_def(
+        &self,
+        name: &str,
+        def: &TypeDef,
+        value: &JsonValue,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        match def {
+            TypeDef::Alias(ty) => self.encode_to(ty, value, out)?,
+            TypeDef::Composite(fields) => {
+                for (field, ty) in fields {
+                    let value = value
+                        .get(field)
+                        .ok_or_else(|| EncodeError::MissingField(name.into(), field.clone()))?;
+                    self.encode_to(ty, value, out)?;
+                }
+            }
+            TypeDef::Variant(variants) => {
+                let (variant, values) = match value {
+                    JsonValue::String(variant) => (variant, None),
+                    JsonValue::Object(map) if map.len() == 1 => {
+                        let (variant, values) = map.iter().next().expect("len is 1; qed");
+                        (variant, Some(values))
+                    }
+                    _ => return Err(invalid(name, value)),
+                };
+                let (index, (_, fields)) = variants
+                    .iter()
+                    .enumerate()
+                    .find(|(_, (v, _))| v == variant)
+                    .ok_or_else(|| EncodeError::UnknownVariant(name.into(), variant.clone()))?;
+                out.push(index as u8);
+                match (fields.as_slice(), values) {
+                    ([], None) => {}
+                    ([ty], Some(value)) => self.encode_to(ty, value, out)?,
+                    (fields, Some(values)) if fields.len() > 1 => {
+                        let values = elements(name, values, Some(fields.len()))?;
+                        for (ty, value) in fields.iter().zip(values) {
+                            self.encode_to(ty, value, out)?;
+                        }
+                    }
+                    _ => return Err(invalid(name, value)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_compact(
+        &self,
+        inner: &TypeName,
+        value: &JsonValue,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        match self.decoder.resolve_primitive(inner)?.as_str() {
+            "u8" => Compact(uint::<u8>("u8", value)?).encode_to(out),
+            "u16" => Compact(uint::<u16>("u16", value)?).encode_to(out),
+            "u32" => Compact(uint::<u32>("u32", value)?).encode_to(out),
+            "u64" => Compact(uint::<u64>("u64", value)?).encode_to(out),
+            "u128" => Compact(uint::<u128>("u128", value)?).encode_to(out),
+            other => {
+                return Err(DecodeError::UnknownType(format!("Compact<{}>", other)).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes `Address` given as an account id, an account index, or the decoded
+    /// `{"Id": ..}` and `{"Index": ..}` variants.
+    fn encode_address(&self, value: &JsonValue, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        match value {
+            JsonValue::String(_) => {
+                out.push(0xff);
+                self.encode_to("AccountId", value, out)?;
+            }
+            JsonValue::Number(_) => match uint::<u64>("Address", value)? {
+                i if i <= 0xef => out.push(i as u8),
+                i if i <= u16::MAX as u64 => (0xfcu8, i as u16).encode_to(out),
+                i if i <= u32::MAX as u64 => (0xfdu8, i as u32).encode_to(out),
+                i => (0xfeu8, i).encode_to(out),
+            },
+            JsonValue::Object(map) if map.len() == 1 => match map.iter().next() {
+                Some((variant, inner)) if variant == "Id" && inner.is_string() => {
+                    self.encode_address(inner, out)?
+                }
+                Some((variant, inner)) if variant == "Index" && inner.is_number() => {
+                    self.encode_address(inner, out)?
+                }
+                _ => return Err(invalid("Address", value)),
+            },
+            _ => return Err(invalid("Address", value)),
+        }
+        Ok(())
+    }
+
+    /// Encodes `Era` given as `"Immortal"` or `{"Mortal": {"period": .., "phase": ..}}`, the
+    /// period is rounded up to a power of two and the phase is quantized like
+    /// `sp_runtime::generic::Era::mortal`.
+    fn encode_era(value: &JsonValue, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        if value.as_str() == Some("Immortal") {
+            out.push(0);
+            return Ok(());
+        }
+        let mortal = value.get("Mortal").ok_or_else(|| invalid("Era", value))?;
+        let field = |field: &str| {
+            mortal
+                .get(field)
+                .ok_or_else(|| EncodeError::MissingField("Era".into(), field.into()))
+                .and_then(|value| uint::<u64>("Era", value))
+        };
+        let period = field("period")?
+            .checked_next_power_of_two()
+            .unwrap_or(1 << 16)
+            .clamp(4, 1 << 16);
+        let phase = field("phase")? % period;
+        let quantize_factor = (period >> 12).max(1);
+        let quantized_phase = phase / quantize_factor * quantize_factor;
+        let encoded = (period.trailing_zeros() - 1).clamp(1, 15) as u16
+            | ((quantized_phase / quantize_factor) << 4) as u16;
+        encoded.encode_to(out);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Value;
+    use serde_json::json;
+
+    #[test]
+    fn encode_value_should_round_trip() {
+        let decoder = ValueDecoder::default();
+        let encoder = ValueEncoder::new(&decoder);
+        let round_trip = |ty: &str, json: JsonValue| {
+            let encoded = encoder.encode(ty, &json).unwrap();
+            let decoded = decoder.decode_all(ty, &encoded).unwrap();
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), json, "{}", ty);
+        };
+
+        round_trip("Compact<Balance>", json!("1000000000000"));
+        round_trip("Vec<(u32, bool)>", json!([[1, true], [2, false]]));
+        round_trip("Option<u16>", json!({ "Some": 7 }));
+        round_trip("Option<u16>", json!("None"));
+        round_trip("Vec<u8>", json!("0x0102"));
+        round_trip("Address", json!({ "Index": 300 }));
+        round_trip("Address", json!({ "Index": 0xef }));
+        round_trip("Address", json!({ "Index": 0xf0 }));
+        assert_eq!(encoder.encode("Address", &json!(0xef)).unwrap(), [0xef]);
+        assert_eq!(
+            encoder.encode("Address", &json!(0xf0)).unwrap(),
+            [0xfc, 0xf0, 0]
+        );
+        round_trip("Era", json!({ "Mortal": { "period": 64, "phase": 42 } }));
+        round_trip(
+            "BTreeMap<u8, String>",
+            json!([{ "key": 1, "value": "one" }]),
+        );
+        round_trip("DispatchClass", json!("Operational"));
+        round_trip(
+            "DispatchInfo",
+            json!({ "weight": 10, "class": "Normal", "pays_fee": "Yes" }),
+        );
+
+        // Alice, by SS58 address and by hex.
+        let alice = "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+        let by_address = encoder
+            .encode(
+                "Address",
+                &json!("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+            )
+            .unwrap();
+        assert_eq!(
+            by_address,
+            encoder.encode("Address", &json!(alice)).unwrap()
+        );
+        assert_eq!(
+            decoder.decode_all("Address", &by_address).unwrap(),
+            Value::Variant(
+                "Id".into(),
+                vec![Value::Bytes(hex::decode(&alice[2..]).unwrap())]
+            )
+        );
+
+        assert_eq!(encoder.encode("Option<u8>", &json!(null)).unwrap(), vec![0]);
+        assert!(matches!(
+            encoder.encode("u8", &json!(256)),
+            Err(EncodeError::InvalidValue(..))
+        ));
+        assert!(matches!(
+            encoder.encode("DispatchClass", &json!("Unknown")),
+            Err(EncodeError::UnknownVariant(..))
+        ));
+        assert!(matches!(
+            encoder.encode("Timepoint", &json!({ "height": 1 })),
+            Err(EncodeError::MissingField(..))
+        ));
+    }
+}
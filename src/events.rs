@@ -0,0 +1,463 @@
+//! Decodes the events of `System::Events`, i.e., `Vec<EventRecord<T::Event, T::Hash>>`.
+//!
+//! The outer `Event` enum of the runtime is indexed by the position of the pallet among the
+//! pallets with events, then the event index within the pallet, followed by the arguments.
+//! [`register_event_types`] registers these enums in the [`ValueDecoder`] from metadata, so
+//! `System::Events` is decoded like any other storage value.
+//!
+//! The events whose argument types are not in the decoder can still be split into the raw
+//! bytes of each argument by [`RawEventsDecoder`], given the sizes of the argument types.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use codec::{Compact, Decode};
+use serde::Serialize;
+use sp_core::H256;
+
+use crate::decoder::{DecodeError, TypeDef, ValueDecoder};
+use crate::metadata::{EventArg, Metadata, MetadataError};
+use crate::value::Value;
+
+/// Error of splitting the raw events.
+#[derive(Debug, thiserror::Error)]
+pub enum EventsError {
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+    /// Failed to decode the phase, the length prefix or the event index.
+    #[error("Failed to decode events: {0}")]
+    Codec(#[from] codec::Error),
+    /// The size of the argument type is not registered.
+    #[error("Size of type {0} not registered")]
+    MissingTypeSize(String),
+    /// The argument is longer than the remaining bytes.
+    #[error("Unexpected end of input")]
+    UnexpectedEof,
+    /// The input is not consumed completely.
+    #[error("Trailing {0} bytes")]
+    TrailingBytes(usize),
+}
+
+/// Value type of `System::Events` in metadata.
+pub const EVENTS_TYPE: &str = "Vec<EventRecord<T::Event, T::Hash>>";
+
+/// Decoded `frame_system::EventRecord`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EventRecord {
+    pub phase: Value,
+    pub pallet: String,
+    pub event: String,
+    pub args: Vec<Value>,
+    pub topics: Vec<H256>,
+}
+
+/// Name of the type of the events of `pallet`.
+fn pallet_event_type(pallet: &str) -> String {
+    format!("{}Event", pallet)
+}
+
+/// Registers `EventRecord` and the `Event` enum of the runtime, plus the event enum of
+/// each pallet, in `decoder`.
+pub fn register_event_types(decoder: &mut ValueDecoder, metadata: &Metadata) {
+    let mut modules = metadata.modules_with_events().collect::<Vec<_>>();
+    modules.sort_by_key(|module| module.index());
+
+    let mut pallets = Vec::with_capacity(modules.len());
+    for module in modules {
+        let events = module
+            .events()
+            .map(|event| {
+                let args = event.arguments().iter().map(ToString::to_string).collect();
+                (event.name.clone(), args)
+            })
+            .collect();
+        let event_ty = pallet_event_type(module.name());
+        decoder.register(event_ty.clone(), TypeDef::Variant(events));
+        pallets.push((module.name().to_string(), vec![event_ty]));
+    }
+    decoder.register("Event", TypeDef::Variant(pallets));
+
+    decoder.register(
+        "EventRecord",
+        TypeDef::Composite(vec![
+            ("phase".into(), "Phase".into()),
+            ("event".into(), "Event".into()),
+            ("topics".into(), "Vec<Hash>".into()),
+        ]),
+    );
+}
+
+impl EventRecord {
+    /// Converts the decoded `EventRecord` value, `value` is given back if it is not an
+    /// `EventRecord` decoded with the types of [`register_event_types`].
+    pub fn from_value(value: Value) -> Result<Self, Value> {
+        if Self::is_record(&value) {
+            Ok(Self::from_record(value).expect("checked by is_record; qed"))
+        } else {
+            Err(value)
+        }
+    }
+
+    fn is_record(value: &Value) -> bool {
+        match value {
+            Value::Composite(fields) if fields.len() == 3 => {
+                let event = match &fields[1].1 {
+                    Value::Variant(_, pallet_event) => {
+                        matches!(pallet_event.as_slice(), [Value::Variant(..)])
+                    }
+                    _ => false,
+                };
+                let topics = match &fields[2].1 {
+                    Value::Sequence(topics) => topics
+                        .iter()
+                        .all(|topic| matches!(topic, Value::Bytes(bytes) if bytes.len() == 32)),
+                    _ => false,
+                };
+                event && topics
+            }
+            _ => false,
+        }
+    }
+
+    fn from_record(value: Value) -> Option<Self> {
+        let mut fields = match value {
+            Value::Composite(fields) if fields.len() == 3 => fields.into_iter().map(|(_, v)| v),
+            _ => return None,
+        };
+        let phase = fields.next()?;
+        let (pallet, event, args) = match fields.next()? {
+            Value::Variant(pallet, mut pallet_event) if pallet_event.len() == 1 => {
+                match pallet_event.pop()? {
+                    Value::Variant(event, args) => (pallet, event, args),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        let topics = match fields.next()? {
+            Value::Sequence(topics) => topics
+                .into_iter()
+                .map(|topic| match topic {
+                    Value::Bytes(bytes) if bytes.len() == 32 => Some(H256::from_slice(&bytes)),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        Some(Self {
+            phase,
+            pallet,
+            event,
+            args,
+            topics,
+        })
+    }
+}
+
+/// Decodes the value of `System::Events`, the event types must have been registered by
+/// [`register_event_types`], otherwise the records fail with [`DecodeError::UnexpectedValue`].
+pub fn decode_events(
+    decoder: &ValueDecoder,
+    encoded: &[u8],
+) -> Result<Vec<EventRecord>, DecodeError> {
+    match decoder.decode_all(EVENTS_TYPE, encoded)? {
+        Value::Sequence(records) => records
+            .into_iter()
+            .map(|record| {
+                EventRecord::from_value(record)
+                    .map_err(|record| DecodeError::UnexpectedValue("EventRecord".into(), record))
+            })
+            .collect(),
+        _ => Err(DecodeError::UnknownType(EVENTS_TYPE.into())),
+    }
+}
+
+/// Sizes of the fixed size event argument types in bytes.
+#[derive(Clone, Debug)]
+pub struct TypeSizes(HashMap<String, usize>);
+
+impl Default for TypeSizes {
+    fn default() -> Self {
+        let sizes = [
+            ("bool", 1),
+            ("u8", 1),
+            ("u16", 2),
+            ("u32", 4),
+            ("u64", 8),
+            ("u128", 16),
+            ("AccountId", 32),
+            ("AccountIndex", 4),
+            ("AuthorityId", 32),
+            ("AuthorityWeight", 8),
+            ("Balance", 16),
+            ("BalanceStatus", 1),
+            ("BlockNumber", 4),
+            ("CallHash", 32),
+            ("DispatchInfo", 10),
+            ("EraIndex", 4),
+            ("EthereumAddress", 20),
+            ("Hash", 32),
+            ("Kind", 16),
+            ("MemberCount", 4),
+            ("PropIndex", 4),
+            ("ProposalIndex", 4),
+            ("ReferendumIndex", 4),
+            ("SessionIndex", 4),
+            ("Timepoint", 8),
+            ("ValidatorId", 32),
+            ("Weight", 8),
+        ];
+        Self(
+            sizes
+                .iter()
+                .map(|(name, size)| (String::from(*name), *size))
+                .collect(),
+        )
+    }
+}
+
+impl TypeSizes {
+    /// Registers the size of type `name`, overrides the existing one if any.
+    pub fn register<S: Into<String>>(&mut self, name: S, size: usize) {
+        self.0.insert(name.into(), size);
+    }
+
+    /// Returns the size of type `name`, the path of the type is ignored if the full name is
+    /// not registered, e.g., `T::AccountId`.
+    pub fn size_of(&self, name: &str) -> Option<usize> {
+        self.0
+            .get(name)
+            .or_else(|| self.0.get(name.rsplit("::").next()?))
+            .copied()
+    }
+}
+
+/// Raw bytes of an event argument.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct RawEventArg {
+    pub ty: String,
+    /// Byte range of the argument in the encoded events.
+    pub range: Range<usize>,
+}
+
+/// Event split into the raw bytes of the arguments.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct RawEvent {
+    pub pallet: String,
+    pub event: String,
+    pub args: Vec<RawEventArg>,
+}
+
+/// Splits the encoded events by the sizes of the argument types, without decoding them.
+pub struct RawEventsDecoder<'a> {
+    metadata: &'a Metadata,
+    pub type_sizes: TypeSizes,
+}
+
+impl<'a> RawEventsDecoder<'a> {
+    pub fn new(metadata: &'a Metadata) -> Self {
+        Self {
+            metadata,
+            type_sizes: TypeSizes::default(),
+        }
+    }
+
+    /// Returns the primitive argument types in metadata whose sizes are not registered.
+    pub fn missing_type_sizes(&self) -> HashSet<String> {
+        self.metadata
+            .modules_with_events()
+            .flat_map(|module| module.events())
+            .flat_map(|event| event.arguments())
+            .flat_map(|arg| arg.primitives())
+            .filter(|ty| self.type_sizes.size_of(ty).is_none())
+            .collect()
+    }
+
+    /// Skips the bytes of `arg` in `input`.
+    fn skip_arg(&self, arg: &EventArg, input: &mut &[u8]) -> Result<(), EventsError> {
+        match arg {
+            EventArg::Primitive(ty) => {
+                let size = self
+                    .type_sizes
+                    .size_of(ty)
+                    .ok_or_else(|| EventsError::MissingTypeSize(ty.clone()))?;
+                let bytes = *input;
+                if bytes.len() < size {
+                    return Err(EventsError::UnexpectedEof);
+                }
+                *input = &bytes[size..];
+            }
+            EventArg::Vec(arg) => {
+                let len = Compact::<u32>::decode(input)?.0;
+                for _ in 0..len {
+                    self.skip_arg(arg, input)?;
+                }
+            }
+            EventArg::Tuple(args) => {
+                for arg in args {
+                    self.skip_arg(arg, input)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Splits the event at the start of `input`, the argument ranges are relative to `encoded`
+    /// of which `input` is the remaining part.
+    fn split_event(&self, encoded: &[u8], input: &mut &[u8]) -> Result<RawEvent, EventsError> {
+        let module = self.metadata.module_with_events(u8::decode(input)?)?;
+        let event = module.event(u8::decode(input)?)?;
+        let mut args = Vec::new();
+        for arg in event.arguments() {
+            let start = encoded.len() - input.len();
+            self.skip_arg(&arg, input)?;
+            args.push(RawEventArg {
+                ty: arg.to_string(),
+                range: start..encoded.len() - input.len(),
+            });
+        }
+        Ok(RawEvent {
+            pallet: module.name().into(),
+            event: event.name.clone(),
+            args,
+        })
+    }
+
+    /// Splits the value of `System::Events` into the events, the phases and topics are skipped.
+    pub fn split_events(&self, encoded: &[u8]) -> Result<Vec<RawEvent>, EventsError> {
+        let input = &mut &encoded[..];
+        let len = Compact::<u32>::decode(input)?.0;
+        let mut events = Vec::new();
+        for _ in 0..len {
+            // `Phase::{ApplyExtrinsic(u32), Finalization, Initialization}`, only
+            // `Phase::ApplyExtrinsic` has a field.
+            match u8::decode(input)? {
+                0 => {
+                    u32::decode(input)?;
+                }
+                1 | 2 => {}
+                _ => return Err(codec::Error::from("Invalid Phase index").into()),
+            }
+            events.push(self.split_event(encoded, input)?);
+            Vec::<H256>::decode(input)?;
+        }
+        if !input.is_empty() {
+            return Err(EventsError::TrailingBytes(input.len()));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::test_metadata;
+    use codec::{Compact, Encode};
+
+    fn event_index(metadata: &Metadata, pallet: &str, event: &str) -> (u8, u8) {
+        let module = &metadata.modules_with_events[pallet];
+        (module.index(), module.event_by_name(event).unwrap().index())
+    }
+
+    #[test]
+    fn decode_events_should_work() {
+        let metadata = test_metadata();
+        let mut decoder = ValueDecoder::default();
+        register_event_types(&mut decoder, &metadata);
+
+        let mut encoded = Compact(2u32).encode();
+        // ApplyExtrinsic(1), Balances::Transfer(from, to, value), no topics.
+        encoded.extend((0u8, 1u32).encode());
+        let (pallet, event) = event_index(&metadata, "Balances", "Transfer");
+        encoded.extend((pallet, event, [1u8; 32], [2u8; 32], 100u128).encode());
+        encoded.extend(Vec::<H256>::new().encode());
+        // Finalization, System::ExtrinsicSuccess(DispatchInfo), one topic.
+        encoded.push(1);
+        let (pallet, event) = event_index(&metadata, "System", "ExtrinsicSuccess");
+        encoded.extend((pallet, event, 10u64, 0u8, 0u8).encode());
+        encoded.extend(vec![H256::repeat_byte(3)].encode());
+
+        let records = decode_events(&decoder, &encoded).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0].phase,
+            Value::Variant("ApplyExtrinsic".into(), vec![1u32.into()])
+        );
+        assert_eq!(records[0].pallet, "Balances");
+        assert_eq!(records[0].event, "Transfer");
+        assert_eq!(
+            records[0].args,
+            vec![
+                Value::Bytes(vec![1; 32]),
+                Value::Bytes(vec![2; 32]),
+                100u128.into()
+            ]
+        );
+        assert!(records[0].topics.is_empty());
+        assert_eq!(records[1].pallet, "System");
+        assert_eq!(records[1].event, "ExtrinsicSuccess");
+        assert_eq!(records[1].topics, vec![H256::repeat_byte(3)]);
+
+        // The topic of a malformed record is not a 32 bytes hash.
+        decoder.register("Hash", TypeDef::Alias("[u8; 4]".into()));
+        let mut encoded = Compact(1u32).encode();
+        encoded.push(1);
+        let (pallet, event) = event_index(&metadata, "Session", "NewSession");
+        encoded.extend((pallet, event, 5u32).encode());
+        encoded.extend(vec![[3u8; 4]].encode());
+        assert!(matches!(
+            decode_events(&decoder, &encoded),
+            Err(DecodeError::UnexpectedValue(..))
+        ));
+    }
+
+    #[test]
+    fn split_raw_events_should_work() {
+        let metadata = test_metadata();
+        let mut decoder = RawEventsDecoder::new(&metadata);
+        assert!(!decoder.missing_type_sizes().contains("AccountId"));
+
+        let mut encoded = Compact(2u32).encode();
+        // Initialization, Balances::Transfer(from, to, value).
+        encoded.push(2);
+        let (pallet, event) = event_index(&metadata, "Balances", "Transfer");
+        encoded.extend((pallet, event, [1u8; 32], [2u8; 32], 100u128).encode());
+        encoded.extend(Vec::<H256>::new().encode());
+        // ApplyExtrinsic(0), Session::NewSession(SessionIndex).
+        encoded.extend((0u8, 0u32).encode());
+        let (pallet, event) = event_index(&metadata, "Session", "NewSession");
+        encoded.extend((pallet, event, 5u32).encode());
+        encoded.extend(Vec::<H256>::new().encode());
+
+        let events = decoder.split_events(&encoded).unwrap();
+        assert_eq!(events[0].pallet, "Balances");
+        assert_eq!(
+            events[0]
+                .args
+                .iter()
+                .map(|arg| arg.range.clone())
+                .collect::<Vec<_>>(),
+            vec![4..36, 36..68, 68..84]
+        );
+        assert_eq!(events[1].event, "NewSession");
+        assert_eq!(
+            &encoded[events[1].args[0].range.clone()],
+            &5u32.encode()[..]
+        );
+
+        let mut invalid_phase = encoded.clone();
+        invalid_phase[1] = 3;
+        assert!(matches!(
+            decoder.split_events(&invalid_phase),
+            Err(EventsError::Codec(_))
+        ));
+
+        decoder.type_sizes = TypeSizes(HashMap::new());
+        assert!(matches!(
+            decoder.split_events(&encoded),
+            Err(EventsError::MissingTypeSize(_))
+        ));
+    }
+}
@@ -0,0 +1,331 @@
+use std::path::{Path, PathBuf};
+
+use crate::decode_storage_key_value::{
+    DecodedKey, DecodedStorage, StorageMetadataLookupTable, TransparentStorageType,
+};
+use crate::decoder::ValueDecoder;
+use crate::state_source::StoragePair;
+
+/// Export error.
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+/// Decoded entries of one storage item, e.g., `System.Account`.
+///
+/// The key columns come first, followed by the flattened value columns. The cell is
+/// empty if the value of that row has no such column.
+#[derive(Clone, Debug, Default)]
+pub struct StorageTable {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl StorageTable {
+    fn push_row(&mut self, cells: Vec<(String, String)>) {
+        let mut row = vec![String::new(); self.columns.len()];
+        for (column, cell) in cells {
+            match self.columns.iter().position(|c| *c == column) {
+                Some(index) => row[index] = cell,
+                None => {
+                    self.columns.push(column);
+                    row.push(cell);
+                }
+            }
+        }
+        self.rows.push(row);
+    }
+
+    /// Returns the rows padded to the final number of columns.
+    fn padded_rows(&self) -> impl Iterator<Item = Vec<&str>> {
+        let width = self.columns.len();
+        self.rows.iter().map(move |row| {
+            let mut cells: Vec<&str> = row.iter().map(String::as_str).collect();
+            cells.resize(width, "");
+            cells
+        })
+    }
+
+    fn file_path(&self, dir: &Path, extension: &str) -> PathBuf {
+        dir.join(format!("{}.{}", self.name, extension))
+    }
+}
+
+/// Returns the key columns of `key`, each key is decoded by its type in `key_types`, or kept in
+/// hex if it can not be decoded, e.g., the key of an opaque hasher.
+fn key_cells(
+    decoder: &ValueDecoder,
+    key: &DecodedKey,
+    key_types: &[&str],
+) -> Vec<(String, String)> {
+    let ty = match key {
+        DecodedKey::WellKnown(key) => return vec![("key".into(), key.to_string())],
+        DecodedKey::Child(key) => return vec![("key".into(), key.to_string())],
+        DecodedKey::Storage(key) => &key.ty,
+    };
+    let keys = match ty {
+        TransparentStorageType::Plain { .. } => Vec::new(),
+        TransparentStorageType::Map { key, .. } => vec![("key", key)],
+        TransparentStorageType::DoubleMap { key1, key2, .. } => {
+            vec![("key1", key1), ("key2", key2)]
+        }
+    };
+    keys.into_iter()
+        .enumerate()
+        .flat_map(|(index, (column, key))| {
+            key_types
+                .get(index)
+                .and_then(|ty| {
+                    let encoded = hex::decode(key).ok()?;
+                    decoder.decode_all(ty, &encoded).ok()
+                })
+                .map(|value| value.flatten(column))
+                .unwrap_or_else(|| vec![(column.into(), format!("0x{}", key))])
+        })
+        .collect()
+}
+
+/// Decodes the storage key/value pairs into one table per storage item.
+pub fn build_tables<I>(
+    table: &StorageMetadataLookupTable,
+    decoder: &ValueDecoder,
+    pairs: I,
+) -> Vec<StorageTable>
+where
+    I: IntoIterator<Item = StoragePair>,
+{
+    let mut tables = table
+        .group_by_prefix(pairs)
+        .into_iter()
+        .filter_map(|(prefix, pairs)| {
+            let storage_metadata = table.lookup(&prefix)?;
+            let key_types: Vec<&str> = storage_metadata
+                .key_types()
+                .into_iter()
+                .map(|(_, ty)| ty)
+                .collect();
+            let mut storage_table = StorageTable {
+                name: format!(
+                    "{}.{}",
+                    storage_metadata.module_prefix, storage_metadata.storage_prefix
+                ),
+                ..Default::default()
+            };
+            for (key, value) in pairs {
+                let raw_value = value.clone();
+                if let Some(DecodedStorage { key, value }) =
+                    table.decode_storage(decoder, key, value)
+                {
+                    let mut cells = key_cells(decoder, &key, &key_types);
+                    match (value, raw_value) {
+                        (Some(value), _) => cells.extend(value.flatten("value")),
+                        (None, Some(raw)) => {
+                            cells.push(("value".into(), format!("0x{}", hex::encode(raw))))
+                        }
+                        (None, None) => {}
+                    }
+                    storage_table.push_row(cells);
+                }
+            }
+            Some(storage_table)
+        })
+        .collect::<Vec<_>>();
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    tables
+}
+
+/// Writes `table` to `<dir>/<table.name>.csv`.
+pub fn write_csv(table: &StorageTable, dir: &Path) -> Result<PathBuf, ExportError> {
+    let path = table.file_path(dir, "csv");
+    let mut writer = csv::Writer::from_path(&path)?;
+    writer.write_record(&table.columns)?;
+    for row in table.padded_rows() {
+        writer.write_record(row)?;
+    }
+    writer.flush()?;
+    Ok(path)
+}
+
+/// Writes `table` to `<dir>/<table.name>.parquet`, all the columns are optional UTF8 strings.
+#[cfg(feature = "parquet")]
+pub fn write_parquet(table: &StorageTable, dir: &Path) -> Result<PathBuf, ExportError> {
+    use std::{fs::File, sync::Arc};
+
+    use parquet::{
+        basic::{LogicalType, Repetition, Type as PhysicalType},
+        column::writer::ColumnWriter,
+        data_type::ByteArray,
+        file::{
+            properties::WriterProperties,
+            writer::{FileWriter, SerializedFileWriter},
+        },
+        schema::types::Type,
+    };
+
+    let mut fields = table
+        .columns
+        .iter()
+        .map(|column| {
+            Type::primitive_type_builder(column, PhysicalType::BYTE_ARRAY)
+                .with_repetition(Repetition::OPTIONAL)
+                .with_logical_type(LogicalType::UTF8)
+                .build()
+                .map(Arc::new)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let schema = Type::group_type_builder("schema")
+        .with_fields(&mut fields)
+        .build()?;
+
+    let path = table.file_path(dir, "parquet");
+    let props = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(File::create(&path)?, Arc::new(schema), props)?;
+    let mut row_group_writer = writer.next_row_group()?;
+
+    let mut column_index = 0;
+    while let Some(mut column_writer) = row_group_writer.next_column()? {
+        let mut values = Vec::new();
+        let mut def_levels = Vec::with_capacity(table.rows.len());
+        for row in &table.rows {
+            match row.get(column_index) {
+                Some(cell) if !cell.is_empty() => {
+                    values.push(ByteArray::from(cell.as_str()));
+                    def_levels.push(1);
+                }
+                _ => def_levels.push(0),
+            }
+        }
+        if let ColumnWriter::ByteArrayColumnWriter(ref mut typed) = column_writer {
+            typed.write_batch(&values, Some(&def_levels), None)?;
+        }
+        row_group_writer.close_column(column_writer)?;
+        column_index += 1;
+    }
+
+    writer.close_row_group(row_group_writer)?;
+    writer.close()?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::test_metadata;
+    use codec::Encode;
+
+    #[test]
+    fn export_csv_should_work() {
+        let metadata = test_metadata();
+        let authored_blocks = |session: u32, validator: u8| {
+            let key = metadata
+                .module("ImOnline")
+                .unwrap()
+                .storage("AuthoredBlocks")
+                .unwrap()
+                .double_map::<u32, [u8; 32]>()
+                .unwrap()
+                .key(&session, &[validator; 32]);
+            hex::encode(key.0)
+        };
+        let now = metadata
+            .module("Timestamp")
+            .unwrap()
+            .storage("Now")
+            .unwrap()
+            .plain()
+            .unwrap()
+            .key();
+        let pairs = vec![
+            (authored_blocks(1, 2), Some(3u32.encode())),
+            (authored_blocks(4, 5), Some(6u32.encode())),
+            (hex::encode(now.0), Some(42u64.encode())),
+        ];
+        let table: StorageMetadataLookupTable = metadata.into();
+        let tables = build_tables(&table, &ValueDecoder::default(), pairs);
+        assert_eq!(
+            tables.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(),
+            ["ImOnline.AuthoredBlocks", "Timestamp.Now"]
+        );
+
+        let dir = std::env::temp_dir().join(format!("export-csv-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = write_csv(&tables[0], &dir).unwrap();
+        assert_eq!(path, dir.join("ImOnline.AuthoredBlocks.csv"));
+
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(reader.headers().unwrap(), vec!["key1", "key2", "value"]);
+        let mut rows = reader
+            .records()
+            .map(|record| record.unwrap().iter().map(String::from).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        rows.sort();
+        let validator = |byte: u8| format!("0x{}", hex::encode([byte; 32]));
+        assert_eq!(
+            rows,
+            [
+                vec!["1".to_string(), validator(2), "3".into()],
+                vec!["4".to_string(), validator(5), "6".into()],
+            ]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn export_parquet_should_work() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::Field;
+
+        let mut table = StorageTable {
+            name: "System.Account".into(),
+            ..Default::default()
+        };
+        table.push_row(vec![("key".into(), "0x01".into())]);
+        table.push_row(vec![
+            ("key".into(), "0x02".into()),
+            ("value.nonce".into(), "3".into()),
+        ]);
+
+        let dir = std::env::temp_dir().join(format!("export-parquet-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = write_parquet(&table, &dir).unwrap();
+        assert_eq!(path, dir.join("System.Account.parquet"));
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let columns = reader
+            .metadata()
+            .file_metadata()
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, ["key", "value.nonce"]);
+
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                row.get_column_iter()
+                    .map(|(_, field)| field.clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                vec![Field::Str("0x01".into()), Field::Null],
+                vec![Field::Str("0x02".into()), Field::Str("3".into())],
+            ]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+//! Decodes the extrinsics, i.e., `UncheckedExtrinsic` of the block bodies.
+//!
+//! The extrinsic is encoded as a byte vector of the version byte, the signature if the top bit
+//! of the version is set, and the call. The signature consists of the address, the signature
+//! and the extra data of the signed extensions.
+
+use std::fmt;
+
+use codec::{Compact, Decode};
+use serde::Serialize;
+
+use crate::calls::{decode_call, CallError, DecodedCall};
+use crate::decoder::{DecodeError, ValueDecoder};
+use crate::metadata::Metadata;
+use crate::value::Value;
+
+/// Version of the extrinsic format.
+pub const EXTRINSIC_VERSION: u8 = 4;
+
+/// Error of decoding an extrinsic.
+#[derive(Debug, thiserror::Error)]
+pub enum ExtrinsicError {
+    /// Only the extrinsic format [`EXTRINSIC_VERSION`] is supported.
+    #[error("Unsupported extrinsic version {0}")]
+    UnsupportedVersion(u8),
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    #[error(transparent)]
+    Call(#[from] CallError),
+}
+
+impl From<codec::Error> for ExtrinsicError {
+    fn from(e: codec::Error) -> Self {
+        DecodeError::from(e).into()
+    }
+}
+
+/// Signature of a signed extrinsic.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExtrinsicSignature {
+    pub address: Value,
+    pub signature: Value,
+    /// `Value::Composite` of the extra data of the signed extensions by name.
+    pub extra: Value,
+}
+
+/// Decoded extrinsic.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DecodedExtrinsic {
+    pub version: u8,
+    /// `None` if the extrinsic is unsigned, e.g., inherents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ExtrinsicSignature>,
+    pub call: DecodedCall,
+}
+
+impl DecodedExtrinsic {
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+}
+
+impl fmt::Display for DecodedExtrinsic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.call)?;
+        if let Some(signature) = &self.signature {
+            write!(f, " signed by {} {}", signature.address, signature.extra)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the extrinsics given the address, signature and signed extension types.
+pub struct ExtrinsicDecoder<'a> {
+    metadata: &'a Metadata,
+    decoder: &'a ValueDecoder,
+    pub address_ty: String,
+    pub signature_ty: String,
+    /// `(name, type)` of the extra data of the signed extensions, the signed extensions
+    /// without extra data, e.g., `CheckSpecVersion`, are omitted.
+    pub extra: Vec<(String, String)>,
+}
+
+impl<'a> ExtrinsicDecoder<'a> {
+    /// Creates the decoder of the Polkadot extrinsics.
+    pub fn new(metadata: &'a Metadata, decoder: &'a ValueDecoder) -> Self {
+        Self {
+            metadata,
+            decoder,
+            address_ty: "Address".into(),
+            signature_ty: "MultiSignature".into(),
+            extra: vec![
+                ("era".into(), "Era".into()),
+                ("nonce".into(), "Compact<Index>".into()),
+                ("tip".into(), "Compact<Balance>".into()),
+            ],
+        }
+    }
+
+    /// Decodes the extrinsic `encoded` without the length prefix.
+    pub fn decode_extrinsic(&self, encoded: &[u8]) -> Result<DecodedExtrinsic, ExtrinsicError> {
+        let input = &mut &encoded[..];
+        let version = u8::decode(input)?;
+        let is_signed = version & 0b1000_0000 != 0;
+        let version = version & 0b0111_1111;
+        if version != EXTRINSIC_VERSION {
+            return Err(ExtrinsicError::UnsupportedVersion(version));
+        }
+
+        let signature = if is_signed {
+            let address = self.decoder.decode(&self.address_ty, input)?;
+            let signature = self.decoder.decode(&self.signature_ty, input)?;
+            let extra = self
+                .extra
+                .iter()
+                .map(|(name, ty)| Ok((name.clone(), self.decoder.decode(ty, input)?)))
+                .collect::<Result<_, DecodeError>>()?;
+            Some(ExtrinsicSignature {
+                address,
+                signature,
+                extra: Value::Composite(extra),
+            })
+        } else {
+            None
+        };
+
+        let call = decode_call(self.metadata, self.decoder, input)?;
+        if !input.is_empty() {
+            return Err(DecodeError::TrailingBytes(input.len()).into());
+        }
+        Ok(DecodedExtrinsic {
+            version,
+            signature,
+            call,
+        })
+    }
+
+    /// Decodes `Vec<UncheckedExtrinsic>`, e.g., the block body.
+    pub fn decode_extrinsics(
+        &self,
+        encoded: &[u8],
+    ) -> Result<Vec<DecodedExtrinsic>, ExtrinsicError> {
+        let input = &mut &encoded[..];
+        let len = Compact::<u32>::decode(input)?.0;
+        let mut extrinsics = Vec::new();
+        for _ in 0..len {
+            extrinsics.push(self.decode_extrinsic(&Vec::<u8>::decode(input)?)?);
+        }
+        if !input.is_empty() {
+            return Err(DecodeError::TrailingBytes(input.len()).into());
+        }
+        Ok(extrinsics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::test_metadata;
+    use codec::Encode;
+
+    #[test]
+    fn decode_extrinsics_should_work() {
+        let metadata = test_metadata();
+        let decoder = ValueDecoder::default();
+        let extrinsic_decoder = ExtrinsicDecoder::new(&metadata, &decoder);
+
+        let module = metadata.module_with_calls("Timestamp").unwrap();
+        let set = module.call_by_name("set").unwrap();
+        let mut unsigned = vec![EXTRINSIC_VERSION, module.index(), set.index()];
+        unsigned.extend(Compact(1_600_000_000_000u64).encode());
+
+        let module = metadata.module_with_calls("Balances").unwrap();
+        let transfer = module.call_by_name("transfer").unwrap();
+        let mut signed = vec![EXTRINSIC_VERSION | 0b1000_0000];
+        // Address::Id, MultiSignature::Sr25519, mortal era, nonce 5 and tip 0.
+        signed.extend((0xffu8, [1u8; 32], 1u8, [2u8; 64]).encode());
+        signed.extend(&[0xa5, 0x02]);
+        signed.extend((Compact(5u32), Compact(0u128)).encode());
+        signed.extend((module.index(), transfer.index(), 0xffu8, [3u8; 32]).encode());
+        signed.extend(Compact(100u128).encode());
+
+        let body = vec![unsigned, signed].encode();
+        let extrinsics = extrinsic_decoder.decode_extrinsics(&body).unwrap();
+        assert_eq!(extrinsics.len(), 2);
+        assert!(!extrinsics[0].is_signed());
+        assert_eq!(extrinsics[0].call.pallet, "Timestamp");
+
+        let signature = extrinsics[1].signature.as_ref().unwrap();
+        assert_eq!(
+            signature.address,
+            Value::Variant("Id".into(), vec![Value::Bytes(vec![1; 32])])
+        );
+        assert_eq!(
+            signature.signature,
+            Value::Variant("Sr25519".into(), vec![Value::Bytes(vec![2; 64])])
+        );
+        match &signature.extra {
+            Value::Composite(extra) => {
+                assert_eq!(extra[1], ("nonce".into(), Value::U32(5)));
+                assert_eq!(extra[2], ("tip".into(), Value::U128(0)));
+            }
+            _ => panic!("Not Composite"),
+        }
+        assert_eq!(extrinsics[1].call.call, "transfer");
+
+        assert!(matches!(
+            extrinsic_decoder.decode_extrinsic(&[3]),
+            Err(ExtrinsicError::UnsupportedVersion(3))
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+//! Recovers the keys of the storage items with the opaque hashers, i.e., `Blake2_128`,
+//! `Blake2_256`, `Twox128` and `Twox256`, from the candidate keys.
+//!
+//! Unlike the concat hashers, the raw key is not appended to the hashed key, so the candidate
+//! keys, e.g., the known accounts, an era range or the asset ids, are hashed by all the opaque
+//! hashers and the hashed keys are looked up by [`KeyPreimages`].
+
+use std::{collections::HashMap, ops::Range};
+
+use frame_metadata::StorageHasher;
+use serde_json::Value as JsonValue;
+
+use crate::encoder::{EncodeError, ValueEncoder};
+use crate::metadata::StorageMetadata;
+
+/// Hashers whose raw key can not be recovered from the hashed key.
+pub const OPAQUE_HASHERS: [StorageHasher; 4] = [
+    StorageHasher::Blake2_128,
+    StorageHasher::Blake2_256,
+    StorageHasher::Twox128,
+    StorageHasher::Twox256,
+];
+
+/// Dictionary of the hashed keys to the SCALE encoded candidate keys.
+#[derive(Clone, Debug, Default)]
+pub struct KeyPreimages(HashMap<Vec<u8>, Vec<u8>>);
+
+impl KeyPreimages {
+    /// Adds the SCALE encoded candidate key.
+    pub fn insert(&mut self, encoded: Vec<u8>) {
+        for hasher in OPAQUE_HASHERS.iter() {
+            self.0
+                .insert(StorageMetadata::hash(hasher, &encoded), encoded.clone());
+        }
+    }
+
+    /// Adds the candidate keys of type `ty` in JSON, e.g., the SS58 addresses of `AccountId`.
+    pub fn insert_json(
+        &mut self,
+        encoder: &ValueEncoder,
+        ty: &str,
+        keys: &[JsonValue],
+    ) -> Result<(), EncodeError> {
+        for key in keys {
+            self.insert(encoder.encode(ty, key)?);
+        }
+        Ok(())
+    }
+
+    /// Adds the candidate keys in `range` of the integer type `ty`, e.g., `EraIndex`.
+    pub fn insert_range(
+        &mut self,
+        encoder: &ValueEncoder,
+        ty: &str,
+        range: Range<u64>,
+    ) -> Result<(), EncodeError> {
+        for key in range {
+            self.insert(encoder.encode(ty, &JsonValue::from(key))?);
+        }
+        Ok(())
+    }
+
+    /// Adds the candidate keys by type in JSON, either an array of the keys or a
+    /// `{"start": .., "end": ..}` range of the integer keys, e.g.,
+    /// `{"AccountId": ["5GrwvaEF..."], "EraIndex": {"start": 0, "end": 100}}`.
+    pub fn insert_all_json(
+        &mut self,
+        encoder: &ValueEncoder,
+        candidates: &JsonValue,
+    ) -> Result<(), EncodeError> {
+        let by_type = candidates
+            .as_object()
+            .ok_or_else(|| EncodeError::InvalidValue("KeyPreimages".into(), candidates.clone()))?;
+        for (ty, keys) in by_type {
+            match keys {
+                JsonValue::Array(keys) => self.insert_json(encoder, ty, keys)?,
+                _ => {
+                    let bound = |bound: &str| {
+                        keys.get(bound)
+                            .and_then(JsonValue::as_u64)
+                            .ok_or_else(|| EncodeError::MissingField(ty.clone(), bound.into()))
+                    };
+                    self.insert_range(encoder, ty, bound("start")?..bound("end")?)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the SCALE encoded key of the opaque `hashed` key.
+    pub fn lookup(&self, hashed: &[u8]) -> Option<&[u8]> {
+        self.0.get(hashed).map(Vec::as_slice)
+    }
+
+    /// Returns the number of the hashed keys.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::ValueDecoder;
+    use codec::Encode;
+    use serde_json::json;
+
+    #[test]
+    fn lookup_key_preimages_should_work() {
+        let decoder = ValueDecoder::default();
+        let encoder = ValueEncoder::new(&decoder);
+        let mut preimages = KeyPreimages::default();
+        preimages
+            .insert_all_json(
+                &encoder,
+                &json!({
+                    "AccountId": [format!("0x{}", hex::encode([1u8; 32]))],
+                    "EraIndex": { "start": 0, "end": 10 },
+                }),
+            )
+            .unwrap();
+        assert_eq!(preimages.len(), 11 * OPAQUE_HASHERS.len());
+
+        let era = 7u32.encode();
+        for hasher in OPAQUE_HASHERS.iter() {
+            assert_eq!(
+                preimages.lookup(&StorageMetadata::hash(hasher, &era)),
+                Some(era.as_slice())
+            );
+        }
+        assert_eq!(
+            preimages.lookup(&sp_core::blake2_256(&[1u8; 32])),
+            Some(&[1u8; 32][..])
+        );
+        assert_eq!(preimages.lookup(&sp_core::twox_128(&10u32.encode())), None);
+
+        assert!(matches!(
+            preimages.insert_all_json(&encoder, &json!({ "EraIndex": { "start": 0 } })),
+            Err(EncodeError::MissingField(..))
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+//! Decodes the substrate storage key/value pairs using the runtime metadata.
+//!
+//! The storage keys are parsed by [`StorageMetadataLookupTable`] into the transparent
+//! key types, and the values are decoded by [`ValueDecoder`] into [`Value`] dynamically.
+
+pub mod calls;
+pub mod chain_spec;
+pub mod child_trie;
+pub mod constants;
+pub mod decode_storage_key_value;
+pub mod decoder;
+pub mod dispatch_error;
+pub mod encoder;
+pub mod events;
+pub mod export;
+pub mod extrinsic;
+pub mod key_preimages;
+pub mod metadata;
+#[cfg(feature = "paritydb")]
+pub mod paritydb_source;
+#[cfg(feature = "rocksdb")]
+pub mod rocksdb_source;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(feature = "wasm")]
+pub mod runtime;
+pub mod snapshot;
+pub mod state_source;
+pub mod storage_key;
+pub mod storage_prefix;
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+pub mod trie_state;
+pub mod value;
+pub mod well_known_keys;
+
+pub use self::calls::{
+    decode_call, decode_call_all, encode_call, register_call_types, DecodedCall,
+};
+pub use self::constants::{decode_constant, decode_constants, DecodedConstant};
+pub use self::decode_storage_key_value::{
+    DecodedKey, DecodedStorage, StorageMetadataLookupTable, TransparentStorageKey,
+    TransparentStorageType,
+};
+pub use self::decoder::{DecodeError, TypeDef, ValueDecoder};
+pub use self::dispatch_error::{register_error_types, ModuleError};
+pub use self::encoder::{EncodeError, ValueEncoder};
+pub use self::events::{decode_events, register_event_types, EventRecord, RawEventsDecoder};
+pub use self::extrinsic::{DecodedExtrinsic, ExtrinsicDecoder};
+pub use self::key_preimages::KeyPreimages;
+pub use self::metadata::{LoadError, Metadata, MetadataError, StorageMetadata};
+pub use self::state_source::{StateSource, StateSourceError, StoragePair};
+pub use self::storage_key::{storage_key, StorageKeyError};
+pub use self::value::Value;
+
+/// Strips the `0x` prefix of a hex string, if any.
+pub fn strip_hex_prefix(s: &str) -> &str {
+    s.strip_prefix("0x").unwrap_or(s)
+}
@@ -1,8 +1,836 @@
-#![allow(unused)]
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    str::FromStr,
+};
 
-mod decode_storage_key_value;
-mod metadata;
+use serde::Serialize;
+use structopt::StructOpt;
 
-fn main() {
-    println!("Hello, world!");
+#[cfg(feature = "paritydb")]
+use test_decode_tx::paritydb_source;
+#[cfg(feature = "rocksdb")]
+use test_decode_tx::rocksdb_source;
+#[cfg(feature = "rpc")]
+use test_decode_tx::rpc;
+#[cfg(feature = "wasm")]
+use test_decode_tx::runtime;
+use test_decode_tx::{
+    chain_spec::ChainSpec, child_trie::ChildTrieRegistry, decode_constants, encode_call, export,
+    register_call_types, register_error_types, register_event_types, snapshot::Snapshot,
+    storage_key, storage_prefix, strip_hex_prefix, DecodedConstant, DecodedKey, DecodedStorage,
+    ExtrinsicDecoder, KeyPreimages, Metadata, StateSource, StorageMetadataLookupTable, StoragePair,
+    ValueDecoder, ValueEncoder,
+};
+
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    /// A single JSON array.
+    Json,
+    /// One JSON object per line.
+    Jsonl,
+    /// Human readable text.
+    Text,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "jsonl" => Ok(Self::Jsonl),
+            "text" => Ok(Self::Text),
+            _ => Err(format!("Unknown output format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ExportFormat {
+    Csv,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            #[cfg(feature = "parquet")]
+            "parquet" => Ok(Self::Parquet),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+// Known child tries.
+#[derive(Debug, StructOpt)]
+struct ChildTrieOpts {
+    /// Register the child tries of crowdloan funds `0..N`.
+    #[structopt(long)]
+    crowdloan_funds: Option<u32>,
+    /// Register the child trie of a contract by its trie id in hex.
+    #[structopt(long)]
+    contract_trie: Vec<String>,
+}
+
+impl ChildTrieOpts {
+    fn registry(&self) -> Result<ChildTrieRegistry, hex::FromHexError> {
+        let mut registry = ChildTrieRegistry::default();
+        if let Some(funds) = self.crowdloan_funds {
+            registry.register_crowdloan_funds(0..funds);
+        }
+        for trie_id in &self.contract_trie {
+            registry.register_contract(hex::decode(strip_hex_prefix(trie_id))?);
+        }
+        Ok(registry)
+    }
+}
+
+// Parsing of the storage keys.
+#[derive(Debug, StructOpt)]
+struct KeyOpts {
+    /// JSON file of the candidate keys by type to recover the keys of the opaque hashers,
+    /// e.g., `{"AccountId": ["5GrwvaEF..."], "EraIndex": {"start": 0, "end": 100}}`.
+    #[structopt(long, parse(from_os_str))]
+    preimages: Option<PathBuf>,
+    /// Verify the keys of the concat hashers by re-hashing them, the storage entries whose
+    /// hashed keys mismatch are reported and skipped.
+    #[structopt(long)]
+    verify_keys: bool,
+}
+
+impl KeyOpts {
+    /// Sets the key preimages, see [`KeyPreimages::insert_all_json`] for the JSON format, and
+    /// the verification of the keys of `table`.
+    fn apply(
+        &self,
+        table: &mut StorageMetadataLookupTable,
+        decoder: &ValueDecoder,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(path) = &self.preimages {
+            let candidates: serde_json::Value =
+                serde_json::from_reader(BufReader::new(File::open(path)?))?;
+            let mut preimages = KeyPreimages::default();
+            preimages.insert_all_json(&ValueEncoder::new(decoder), &candidates)?;
+            eprintln!("{} hashed keys of the candidate keys", preimages.len());
+            table.set_preimages(preimages);
+        }
+        table.set_verify_keys(self.verify_keys);
+        Ok(())
+    }
+}
+
+// Output of the decoded storage.
+#[derive(Debug, StructOpt)]
+struct OutputOpts {
+    /// Output format.
+    #[structopt(long, default_value = "text", possible_values = &["json", "jsonl", "text"])]
+    format: OutputFormat,
+    /// Include the documentation of the storage items in metadata.
+    #[structopt(long)]
+    docs: bool,
+}
+
+// Only decode the entries of a storage item.
+#[derive(Debug, StructOpt)]
+struct StorageFilter {
+    /// Pallet of the storage item, e.g., `System`.
+    #[structopt(long, requires = "item")]
+    pallet: Option<String>,
+    /// Name of the storage item, e.g., `Account`.
+    #[structopt(long, requires = "pallet")]
+    item: Option<String>,
+    /// Only decode the entries of the DoubleMap with this key1, in JSON, e.g., an SS58 address
+    /// or a number, or the SCALE encoded key1 in hex.
+    #[structopt(long, requires = "item")]
+    key1: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "storage-decode",
+    about = "Decode substrate storage key/value pairs"
+)]
+enum Command {
+    /// Decode the storage key/value pairs, one `<key> [value]` pair in hex per line.
+    Decode {
+        /// Path of the runtime metadata, in SCALE, hex or the `state_getMetadata` response,
+        /// or the runtime WASM blob with the `wasm` feature.
+        #[structopt(long, parse(from_os_str))]
+        metadata: PathBuf,
+        /// Input file, read from stdin if omitted.
+        #[structopt(parse(from_os_str))]
+        input: Option<PathBuf>,
+        #[structopt(flatten)]
+        output: OutputOpts,
+        /// Decode the input as the pairs of this child trie, either the child trie id or
+        /// the child storage key in hex.
+        #[structopt(long)]
+        child: Option<String>,
+        #[structopt(flatten)]
+        child_tries: ChildTrieOpts,
+        #[structopt(flatten)]
+        key_opts: KeyOpts,
+        #[structopt(flatten)]
+        filter: StorageFilter,
+    },
+    /// Export the decoded state dump as one table per storage item.
+    Export {
+        /// Path of the runtime metadata, in SCALE, hex or the `state_getMetadata` response,
+        /// or the runtime WASM blob with the `wasm` feature.
+        #[structopt(long, parse(from_os_str))]
+        metadata: PathBuf,
+        /// Input file, read from stdin if omitted.
+        #[structopt(parse(from_os_str))]
+        input: Option<PathBuf>,
+        /// Directory of the exported files.
+        #[structopt(long, parse(from_os_str))]
+        out_dir: PathBuf,
+        /// Export format, `parquet` requires the `parquet` feature.
+        #[structopt(long, default_value = "csv")]
+        format: ExportFormat,
+        #[structopt(flatten)]
+        key_opts: KeyOpts,
+    },
+    /// Decode the pallet constants in metadata.
+    Constants {
+        /// Path of the runtime metadata, in SCALE, hex or the `state_getMetadata` response,
+        /// or the runtime WASM blob with the `wasm` feature.
+        #[structopt(long, parse(from_os_str))]
+        metadata: PathBuf,
+        /// Only decode the constants of this pallet, e.g., `Balances`.
+        #[structopt(long)]
+        pallet: Option<String>,
+        #[structopt(flatten)]
+        output: OutputOpts,
+    },
+    /// Decode the extrinsics of the block bodies, one SCALE encoded `Vec<Extrinsic>` in hex
+    /// per line.
+    Extrinsics {
+        /// Path of the runtime metadata, in SCALE, hex or the `state_getMetadata` response,
+        /// or the runtime WASM blob with the `wasm` feature.
+        #[structopt(long, parse(from_os_str))]
+        metadata: PathBuf,
+        /// Input file, read from stdin if omitted.
+        #[structopt(parse(from_os_str))]
+        input: Option<PathBuf>,
+        /// Output format.
+        #[structopt(long, default_value = "text", possible_values = &["json", "jsonl", "text"])]
+        format: OutputFormat,
+    },
+    /// Encode a call from the JSON arguments and print it in hex.
+    EncodeCall {
+        /// Path of the runtime metadata, in SCALE, hex or the `state_getMetadata` response,
+        /// or the runtime WASM blob with the `wasm` feature.
+        #[structopt(long, parse(from_os_str))]
+        metadata: PathBuf,
+        /// Pallet of the call, e.g., `Balances`.
+        #[structopt(long)]
+        pallet: String,
+        /// Name of the call, e.g., `transfer`.
+        #[structopt(long)]
+        call: String,
+        /// Arguments in JSON, either an object by the argument names or an array in order.
+        #[structopt(default_value = "[]")]
+        args: String,
+    },
+    /// Build the storage key of a storage item and print it in hex.
+    StorageKey {
+        /// Path of the runtime metadata, in SCALE, hex or the `state_getMetadata` response,
+        /// or the runtime WASM blob with the `wasm` feature.
+        #[structopt(long, parse(from_os_str))]
+        metadata: PathBuf,
+        /// Pallet of the storage item, e.g., `System`.
+        #[structopt(long)]
+        pallet: String,
+        /// Name of the storage item, e.g., `Account`.
+        #[structopt(long)]
+        item: String,
+        /// Keys in JSON, e.g., an SS58 address or a number, or the SCALE encoded keys in hex.
+        /// The prefix of the entries is printed if fewer keys are given.
+        keys: Vec<String>,
+    },
+    /// Decode the genesis storage of a raw chain spec.
+    ChainSpec {
+        /// Path of the raw chain spec JSON file.
+        #[structopt(parse(from_os_str))]
+        chain_spec: PathBuf,
+        /// Path of the runtime metadata, extracted from `:code` with the `wasm` feature if omitted.
+        #[structopt(long, parse(from_os_str))]
+        metadata: Option<PathBuf>,
+        #[structopt(flatten)]
+        output: OutputOpts,
+        #[structopt(flatten)]
+        child_tries: ChildTrieOpts,
+        #[structopt(flatten)]
+        key_opts: KeyOpts,
+        #[structopt(flatten)]
+        filter: StorageFilter,
+    },
+    /// Decode the state snapshot produced by `try-runtime`.
+    Snapshot {
+        /// Path of the `.snap` file.
+        #[structopt(parse(from_os_str))]
+        snapshot: PathBuf,
+        /// Path of the runtime metadata, in SCALE, hex or the `state_getMetadata` response,
+        /// or the runtime WASM blob with the `wasm` feature.
+        #[structopt(long, parse(from_os_str))]
+        metadata: PathBuf,
+        #[structopt(flatten)]
+        output: OutputOpts,
+        #[structopt(flatten)]
+        child_tries: ChildTrieOpts,
+        #[structopt(flatten)]
+        key_opts: KeyOpts,
+        #[structopt(flatten)]
+        filter: StorageFilter,
+    },
+    /// Decode the state of a block of a running node via JSON-RPC.
+    #[cfg(feature = "rpc")]
+    Rpc {
+        /// HTTP endpoint of the node.
+        #[structopt(long, default_value = "http://localhost:9933")]
+        url: String,
+        /// Block hash, the best block if omitted.
+        #[structopt(long)]
+        block: Option<String>,
+        /// Only decode the keys starting with this prefix in hex.
+        #[structopt(long, default_value = "")]
+        prefix: String,
+        /// Path of the runtime metadata, fetched from the node if omitted.
+        #[structopt(long, parse(from_os_str))]
+        metadata: Option<PathBuf>,
+        #[structopt(flatten)]
+        output: OutputOpts,
+        #[structopt(flatten)]
+        child_tries: ChildTrieOpts,
+        #[structopt(flatten)]
+        key_opts: KeyOpts,
+        #[structopt(flatten)]
+        filter: StorageFilter,
+    },
+    /// Decode the state of a block in the RocksDB database of a stopped node.
+    #[cfg(feature = "rocksdb")]
+    #[structopt(name = "rocksdb")]
+    RocksDb {
+        /// Path of the database, i.e., `<base-path>/chains/<chain>/db/full`.
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Block hash, the best block if omitted.
+        #[structopt(long)]
+        block: Option<String>,
+        /// Path of the runtime metadata, in SCALE, hex or the `state_getMetadata` response,
+        /// or the runtime WASM blob with the `wasm` feature.
+        #[structopt(long, parse(from_os_str))]
+        metadata: PathBuf,
+        #[structopt(flatten)]
+        output: OutputOpts,
+        #[structopt(flatten)]
+        child_tries: ChildTrieOpts,
+        #[structopt(flatten)]
+        key_opts: KeyOpts,
+        #[structopt(flatten)]
+        filter: StorageFilter,
+    },
+    /// Decode the state at a state root in the ParityDB database of a stopped node.
+    #[cfg(feature = "paritydb")]
+    #[structopt(name = "paritydb")]
+    ParityDb {
+        /// Path of the database, i.e., `<base-path>/chains/<chain>/paritydb/full`.
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// State root of the block in hex.
+        #[structopt(long)]
+        state_root: String,
+        /// Path of the runtime metadata, in SCALE, hex or the `state_getMetadata` response,
+        /// or the runtime WASM blob with the `wasm` feature.
+        #[structopt(long, parse(from_os_str))]
+        metadata: PathBuf,
+        #[structopt(flatten)]
+        output: OutputOpts,
+        #[structopt(flatten)]
+        child_tries: ChildTrieOpts,
+        #[structopt(flatten)]
+        key_opts: KeyOpts,
+        #[structopt(flatten)]
+        filter: StorageFilter,
+    },
+}
+
+/// Loads the metadata from a metadata file, or a runtime WASM blob if the `wasm` feature
+/// is enabled.
+fn load_metadata(path: &PathBuf) -> Result<Metadata, Box<dyn Error>> {
+    #[cfg(feature = "wasm")]
+    {
+        let mut magic = [0u8; 4];
+        let is_wasm = File::open(path)
+            .and_then(|mut f| io::Read::read_exact(&mut f, &mut magic))
+            .map(|_| runtime::is_wasm(&magic))
+            .unwrap_or(false);
+        if is_wasm {
+            return Ok(runtime::load_metadata(path)?);
+        }
+    }
+    Ok(Metadata::load(path)?)
+}
+
+/// Returns the metadata of the genesis runtime in `chain_spec` unless `metadata` is specified.
+#[cfg_attr(not(feature = "wasm"), allow(unused_variables))]
+fn chain_spec_metadata(
+    chain_spec: &ChainSpec,
+    metadata: Option<PathBuf>,
+) -> Result<Metadata, Box<dyn Error>> {
+    if let Some(path) = metadata {
+        return load_metadata(&path);
+    }
+
+    #[cfg(feature = "wasm")]
+    return Ok(runtime::metadata_from_wasm(&chain_spec.code()?)?);
+
+    #[cfg(not(feature = "wasm"))]
+    Err(
+        "Extracting the metadata from `:code` requires the `wasm` feature, \
+        please specify --metadata instead"
+            .into(),
+    )
+}
+
+/// Returns the value decoder with the call, event and error types of `metadata`.
+fn value_decoder(metadata: &Metadata) -> ValueDecoder {
+    let mut decoder = ValueDecoder::default();
+    register_call_types(&mut decoder, metadata);
+    register_event_types(&mut decoder, metadata);
+    register_error_types(&mut decoder, metadata);
+    decoder
+}
+
+/// Returns the storage lookup table and the value decoder of `metadata`.
+fn lookup_table(metadata: Metadata) -> (StorageMetadataLookupTable, ValueDecoder) {
+    let decoder = value_decoder(&metadata);
+    (metadata.into(), decoder)
+}
+
+/// Parses a line of `<key> [value]` in hex, the key is checked to be hex but kept as a string.
+fn parse_line(line: &str) -> Option<Result<StoragePair, hex::FromHexError>> {
+    let mut parts = line.split_whitespace();
+    let key = parts.next()?;
+    if key.starts_with('#') {
+        return None;
+    }
+    let key = strip_hex_prefix(key).to_lowercase();
+    if let Err(e) = hex::decode(&key) {
+        return Some(Err(e));
+    }
+    Some(match parts.next() {
+        Some(value) => hex::decode(strip_hex_prefix(value)).map(|value| (key, Some(value))),
+        None => Ok((key, None)),
+    })
+}
+
+/// Opens `input`, or stdin if `input` is `None`.
+fn open_input(input: Option<PathBuf>) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+    Ok(match input {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(BufReader::new(io::stdin())),
+    })
+}
+
+/// Reads the hex blobs from `input`, one per line.
+fn read_blobs(input: Option<PathBuf>) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    let mut blobs = Vec::new();
+    for line in open_input(input)?.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        blobs.push(hex::decode(strip_hex_prefix(line))?);
+    }
+    Ok(blobs)
+}
+
+/// Reads the `<key> [value]` pairs from `input`, or stdin if `input` is `None`.
+fn read_pairs(input: Option<PathBuf>) -> Result<Vec<StoragePair>, Box<dyn Error>> {
+    let mut pairs = Vec::new();
+    for line in open_input(input)?.lines() {
+        if let Some(pair) = parse_line(&line?) {
+            pairs.push(pair?);
+        }
+    }
+    Ok(pairs)
+}
+
+/// Decodes the top trie pairs, the child trie roots are annotated with their owners.
+fn decode_top_pairs(
+    table: &StorageMetadataLookupTable,
+    decoder: &ValueDecoder,
+    registry: &ChildTrieRegistry,
+    pairs: Vec<StoragePair>,
+) -> Vec<DecodedStorage> {
+    pairs
+        .into_iter()
+        .filter_map(|(key, value)| table.decode_storage(decoder, key, value))
+        .map(|mut decoded| {
+            registry.annotate(&mut decoded);
+            decoded
+        })
+        .collect()
+}
+
+/// Decodes both the top trie and the child tries of `source`, or only the storage item
+/// of `filter` if specified.
+fn decode_state(
+    source: &impl StateSource,
+    table: &StorageMetadataLookupTable,
+    decoder: &ValueDecoder,
+    registry: &ChildTrieRegistry,
+    filter: &StorageFilter,
+) -> Result<Vec<DecodedStorage>, Box<dyn Error>> {
+    if let (Some(pallet), Some(item)) = (&filter.pallet, &filter.item) {
+        let decoded = match &filter.key1 {
+            Some(key1) => {
+                let key1 = storage_key::parse_key_arg(key1);
+                let prefix = storage_key(table, decoder, pallet, item, &[key1])?;
+                storage_prefix::iter_key_prefix(source, table, decoder, &prefix.0)?.collect()
+            }
+            None => storage_prefix::iter_prefix(source, table, decoder, pallet, item)?.collect(),
+        };
+        return Ok(decoded);
+    }
+
+    let mut decoded = decode_top_pairs(table, decoder, registry, source.top_pairs()?);
+    decoded.extend(
+        source
+            .child_triples()?
+            .into_iter()
+            .map(|(child_info, key, value)| {
+                registry.decode_child_storage(decoder, &child_info, key, Some(value))
+            }),
+    );
+    Ok(decoded)
+}
+
+/// Decoded storage with the documentation of the storage item.
+#[derive(Serialize)]
+struct Documented<'a> {
+    #[serde(flatten)]
+    decoded: &'a DecodedStorage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    documentation: Option<&'a [String]>,
+}
+
+fn write_decoded(
+    output: &OutputOpts,
+    table: &StorageMetadataLookupTable,
+    decoded: &[DecodedStorage],
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let documented = decoded.iter().map(|decoded| Documented {
+        decoded,
+        documentation: match &decoded.key {
+            DecodedKey::Storage(key) if output.docs => Some(table.documentation(key)),
+            _ => None,
+        },
+    });
+    match output.format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &documented.collect::<Vec<_>>())?;
+            writeln!(out)?;
+        }
+        OutputFormat::Jsonl => {
+            for item in documented {
+                serde_json::to_writer(&mut *out, &item)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Text => {
+            for item in documented {
+                for line in item.documentation.unwrap_or(&[]) {
+                    writeln!(out, "///{}", line)?;
+                }
+                writeln!(out, "{}", item.decoded)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the items in `format`, the text format is their `Display`.
+fn write_items<T: Serialize + fmt::Display>(
+    format: OutputFormat,
+    items: &[T],
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, items)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Jsonl => {
+            for item in items {
+                serde_json::to_writer(&mut *out, item)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Text => {
+            for item in items {
+                writeln!(out, "{}", item)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_constants(
+    output: &OutputOpts,
+    constants: &mut [DecodedConstant],
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if !output.docs {
+        for constant in constants.iter_mut() {
+            constant.documentation.clear();
+        }
+    }
+    if let OutputFormat::Text = output.format {
+        for constant in constants.iter() {
+            for line in &constant.documentation {
+                writeln!(out, "///{}", line)?;
+            }
+            writeln!(out, "{}", constant)?;
+        }
+        return Ok(());
+    }
+    write_items(output.format, constants, out)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    match Command::from_args() {
+        Command::Decode {
+            metadata,
+            input,
+            output,
+            child,
+            child_tries,
+            key_opts,
+            filter,
+        } => {
+            let (mut table, decoder) = lookup_table(load_metadata(&metadata)?);
+            key_opts.apply(&mut table, &decoder)?;
+            let registry = child_tries.registry()?;
+
+            let pairs = read_pairs(input)?;
+            let decoded = match child {
+                Some(child_info) => {
+                    let child_info = hex::decode(strip_hex_prefix(&child_info))?;
+                    pairs
+                        .into_iter()
+                        .map(|(key, value)| {
+                            Ok(registry.decode_child_storage(
+                                &decoder,
+                                &child_info,
+                                hex::decode(key)?,
+                                value,
+                            ))
+                        })
+                        .collect::<Result<Vec<_>, hex::FromHexError>>()?
+                }
+                None => decode_state(&pairs, &table, &decoder, &registry, &filter)?,
+            };
+
+            let stdout = io::stdout();
+            write_decoded(&output, &table, &decoded, &mut stdout.lock())?;
+        }
+        Command::Export {
+            metadata,
+            input,
+            out_dir,
+            format,
+            key_opts,
+        } => {
+            let (mut table, decoder) = lookup_table(load_metadata(&metadata)?);
+            key_opts.apply(&mut table, &decoder)?;
+
+            std::fs::create_dir_all(&out_dir)?;
+            for storage_table in export::build_tables(&table, &decoder, read_pairs(input)?) {
+                let path = match format {
+                    ExportFormat::Csv => export::write_csv(&storage_table, &out_dir)?,
+                    #[cfg(feature = "parquet")]
+                    ExportFormat::Parquet => export::write_parquet(&storage_table, &out_dir)?,
+                };
+                eprintln!("{} rows => {}", storage_table.rows.len(), path.display());
+            }
+        }
+        Command::Constants {
+            metadata,
+            pallet,
+            output,
+        } => {
+            let metadata = load_metadata(&metadata)?;
+            let decoder = value_decoder(&metadata);
+            let mut constants = decode_constants(&metadata, &decoder, pallet.as_deref())?;
+            let stdout = io::stdout();
+            write_constants(&output, &mut constants, &mut stdout.lock())?;
+        }
+        Command::Extrinsics {
+            metadata,
+            input,
+            format,
+        } => {
+            let metadata = load_metadata(&metadata)?;
+            let decoder = value_decoder(&metadata);
+            let extrinsic_decoder = ExtrinsicDecoder::new(&metadata, &decoder);
+
+            let mut extrinsics = Vec::new();
+            for body in read_blobs(input)? {
+                extrinsics.extend(extrinsic_decoder.decode_extrinsics(&body)?);
+            }
+            let stdout = io::stdout();
+            write_items(format, &extrinsics, &mut stdout.lock())?;
+        }
+        Command::EncodeCall {
+            metadata,
+            pallet,
+            call,
+            args,
+        } => {
+            let metadata = load_metadata(&metadata)?;
+            let decoder = value_decoder(&metadata);
+            let args = serde_json::from_str(&args)?;
+            let encoded = encode_call(&metadata, &decoder, &pallet, &call, &args)?;
+            println!("0x{}", hex::encode(encoded.0));
+        }
+        Command::StorageKey {
+            metadata,
+            pallet,
+            item,
+            keys,
+        } => {
+            let (table, decoder) = lookup_table(load_metadata(&metadata)?);
+            let keys = keys
+                .iter()
+                .map(|key| storage_key::parse_key_arg(key))
+                .collect::<Vec<_>>();
+            let key = storage_key(&table, &decoder, &pallet, &item, &keys)?;
+            println!("0x{}", hex::encode(key.0));
+        }
+        Command::ChainSpec {
+            chain_spec,
+            metadata,
+            output,
+            child_tries,
+            key_opts,
+            filter,
+        } => {
+            let chain_spec = ChainSpec::load(&chain_spec)?;
+            let (mut table, decoder) = lookup_table(chain_spec_metadata(&chain_spec, metadata)?);
+            key_opts.apply(&mut table, &decoder)?;
+            let registry = child_tries.registry()?;
+
+            let decoded = decode_state(&chain_spec, &table, &decoder, &registry, &filter)?;
+
+            let stdout = io::stdout();
+            write_decoded(&output, &table, &decoded, &mut stdout.lock())?;
+        }
+        Command::Snapshot {
+            snapshot,
+            metadata,
+            output,
+            child_tries,
+            key_opts,
+            filter,
+        } => {
+            let snapshot = Snapshot::load(&snapshot)?;
+            if let Some(state_version) = snapshot.state_version {
+                eprintln!("State version: {:?}", state_version);
+            }
+
+            let (mut table, decoder) = lookup_table(load_metadata(&metadata)?);
+            key_opts.apply(&mut table, &decoder)?;
+            let registry = child_tries.registry()?;
+            let decoded = decode_state(&snapshot, &table, &decoder, &registry, &filter)?;
+
+            let stdout = io::stdout();
+            write_decoded(&output, &table, &decoded, &mut stdout.lock())?;
+        }
+        #[cfg(feature = "rpc")]
+        Command::Rpc {
+            url,
+            block,
+            prefix,
+            metadata,
+            output,
+            child_tries,
+            key_opts,
+            filter,
+        } => {
+            let client = rpc::RpcClient::new(url);
+            let metadata = match metadata {
+                Some(path) => load_metadata(&path)?,
+                None => client.metadata(block.as_deref())?,
+            };
+            let mut state = rpc::RpcState::new(client, block);
+            state.prefix = prefix;
+
+            let (mut table, decoder) = lookup_table(metadata);
+            key_opts.apply(&mut table, &decoder)?;
+            let registry = child_tries.registry()?;
+            let decoded = decode_state(&state, &table, &decoder, &registry, &filter)?;
+
+            let stdout = io::stdout();
+            write_decoded(&output, &table, &decoded, &mut stdout.lock())?;
+        }
+        #[cfg(feature = "rocksdb")]
+        Command::RocksDb {
+            path,
+            block,
+            metadata,
+            output,
+            child_tries,
+            key_opts,
+            filter,
+        } => {
+            let block = block
+                .map(|hash| sp_core::H256::from_str(strip_hex_prefix(&hash)))
+                .transpose()?;
+            let state = rocksdb_source::open(&path, block)?;
+            eprintln!("State root: {:?}", state.root());
+
+            let (mut table, decoder) = lookup_table(load_metadata(&metadata)?);
+            key_opts.apply(&mut table, &decoder)?;
+            let registry = child_tries.registry()?;
+            let decoded = decode_state(&state, &table, &decoder, &registry, &filter)?;
+
+            let stdout = io::stdout();
+            write_decoded(&output, &table, &decoded, &mut stdout.lock())?;
+        }
+        #[cfg(feature = "paritydb")]
+        Command::ParityDb {
+            path,
+            state_root,
+            metadata,
+            output,
+            child_tries,
+            key_opts,
+            filter,
+        } => {
+            let state_root = sp_core::H256::from_str(strip_hex_prefix(&state_root))?;
+            let state = paritydb_source::open(&path, state_root)?;
+
+            let (mut table, decoder) = lookup_table(load_metadata(&metadata)?);
+            key_opts.apply(&mut table, &decoder)?;
+            let registry = child_tries.registry()?;
+            let decoded = decode_state(&state, &table, &decoder, &registry, &filter)?;
+
+            let stdout = io::stdout();
+            write_decoded(&output, &table, &decoded, &mut stdout.lock())?;
+        }
+    }
+    Ok(())
 }
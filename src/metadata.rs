@@ -2,9 +2,12 @@
 //
 // Expose some fields in structs
 
-use std::{collections::HashMap, convert::TryFrom, marker::PhantomData, str::FromStr};
+use std::{
+    collections::HashMap, convert::TryFrom, fmt, marker::PhantomData, path::Path, str::FromStr,
+};
 
 use codec::{Decode, Encode, Error as CodecError};
+use serde::Serialize;
 
 use frame_metadata::{
     DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryModifier,
@@ -37,16 +40,28 @@ pub enum MetadataError {
     ModuleIndexNotFound(u8),
     /// Call is not in metadata.
     #[error("Call {0} not found")]
-    CallNotFound(&'static str),
+    CallNotFound(String),
+    /// Call is not in metadata.
+    #[error("Call index {0} not found")]
+    CallIndexNotFound(u8),
     /// Event is not in metadata.
     #[error("Event {0} not found")]
     EventNotFound(u8),
     /// Event is not in metadata.
+    #[error("Event {0} not found")]
+    EventNameNotFound(String),
+    /// Error is not in metadata.
     #[error("Error {0} not found")]
     ErrorNotFound(u8),
+    /// Error is not in metadata.
+    #[error("Error {0} not found")]
+    ErrorNameNotFound(String),
     /// Storage is not in metadata.
     #[error("Storage {0} not found")]
     StorageNotFound(&'static str),
+    /// Constant is not in metadata.
+    #[error("Constant {0} not found")]
+    ConstantNotFound(String),
     /// Storage type does not match requested type.
     #[error("Storage type error")]
     StorageTypeError,
@@ -55,6 +70,32 @@ pub enum MetadataError {
     DefaultError(CodecError),
 }
 
+/// Error of loading the metadata from file.
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    /// Failed to read the file.
+    #[error("Failed to read metadata file: {0}")]
+    Io(#[from] std::io::Error),
+    /// Text file is neither hex nor JSON.
+    #[error("Invalid hex metadata: {0}")]
+    InvalidHex(#[from] hex::FromHexError),
+    /// Failed to parse the JSON-RPC response.
+    #[error("Invalid JSON-RPC response: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    /// JSON-RPC response is an error object.
+    #[error("JSON-RPC error response: {0}")]
+    RpcError(String),
+    /// JSON-RPC response has no `result` in hex string.
+    #[error("JSON-RPC response has no result string")]
+    MissingResult,
+    /// Failed to decode `RuntimeMetadataPrefixed`.
+    #[error("Failed to decode metadata: {0}")]
+    Decode(#[from] CodecError),
+    /// Failed to convert the decoded metadata.
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+}
+
 /// Runtime metadata.
 #[derive(Clone, Debug)]
 pub struct Metadata {
@@ -65,6 +106,48 @@ pub struct Metadata {
 }
 
 impl Metadata {
+    /// Loads the metadata from file, accepts:
+    ///
+    /// - raw SCALE encoded `RuntimeMetadataPrefixed`.
+    /// - hex string of the SCALE encoded metadata, with or without `0x`.
+    /// - the saved JSON-RPC response of `state_getMetadata`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Parses the metadata in any of the formats accepted by [`Metadata::load`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LoadError> {
+        // SCALE encoded metadata always starts with the magic number `meta`.
+        if bytes.starts_with(&META_RESERVED.to_le_bytes()) {
+            return Self::from_scale(bytes);
+        }
+
+        let text = String::from_utf8_lossy(bytes);
+        let text = text.trim();
+        let hex_str = if text.starts_with('{') {
+            let response: serde_json::Value = serde_json::from_str(text)?;
+            if let Some(error) = response.get("error") {
+                return Err(LoadError::RpcError(error.to_string()));
+            }
+            response
+                .get("result")
+                .and_then(|result| result.as_str())
+                .ok_or(LoadError::MissingResult)?
+                .to_string()
+        } else {
+            text.to_string()
+        };
+
+        let scale = hex::decode(hex_str.strip_prefix("0x").unwrap_or(&hex_str))?;
+        Self::from_scale(&scale)
+    }
+
+    fn from_scale(mut bytes: &[u8]) -> Result<Self, LoadError> {
+        let prefixed = RuntimeMetadataPrefixed::decode(&mut bytes)?;
+        Ok(Self::try_from(prefixed)?)
+    }
+
     /// Returns `ModuleMetadata`.
     pub fn module<S>(&self, name: S) -> Result<&ModuleMetadata, MetadataError>
     where
@@ -76,7 +159,7 @@ impl Metadata {
             .ok_or(MetadataError::ModuleNotFound(name))
     }
 
-    pub(crate) fn module_with_calls<S>(&self, name: S) -> Result<&ModuleWithCalls, MetadataError>
+    pub fn module_with_calls<S>(&self, name: S) -> Result<&ModuleWithCalls, MetadataError>
     where
         S: ToString,
     {
@@ -86,24 +169,28 @@ impl Metadata {
             .ok_or(MetadataError::ModuleNotFound(name))
     }
 
-    pub(crate) fn modules_with_events(&self) -> impl Iterator<Item = &ModuleWithEvents> {
+    pub fn module_with_calls_by_index(
+        &self,
+        module_index: u8,
+    ) -> Result<&ModuleWithCalls, MetadataError> {
+        self.modules_with_calls
+            .values()
+            .find(|&module| module.index == module_index)
+            .ok_or(MetadataError::ModuleIndexNotFound(module_index))
+    }
+
+    pub fn modules_with_events(&self) -> impl Iterator<Item = &ModuleWithEvents> {
         self.modules_with_events.values()
     }
 
-    pub(crate) fn module_with_events(
-        &self,
-        module_index: u8,
-    ) -> Result<&ModuleWithEvents, MetadataError> {
+    pub fn module_with_events(&self, module_index: u8) -> Result<&ModuleWithEvents, MetadataError> {
         self.modules_with_events
             .values()
             .find(|&module| module.index == module_index)
             .ok_or(MetadataError::ModuleIndexNotFound(module_index))
     }
 
-    pub(crate) fn module_with_errors(
-        &self,
-        module_index: u8,
-    ) -> Result<&ModuleWithErrors, MetadataError> {
+    pub fn module_with_errors(&self, module_index: u8) -> Result<&ModuleWithErrors, MetadataError> {
         self.modules_with_errors
             .values()
             .find(|&module| module.index == module_index)
@@ -144,7 +231,7 @@ impl Metadata {
 pub struct ModuleMetadata {
     pub name: String,
     pub storage: HashMap<String, StorageMetadata>,
-    // constants
+    pub constants: HashMap<String, ModuleConstantMetadata>,
 }
 
 impl ModuleMetadata {
@@ -153,15 +240,69 @@ impl ModuleMetadata {
             .get(key)
             .ok_or(MetadataError::StorageNotFound(key))
     }
+
+    pub fn constant(&self, name: &str) -> Result<&ModuleConstantMetadata, MetadataError> {
+        self.constants
+            .get(name)
+            .ok_or_else(|| MetadataError::ConstantNotFound(name.into()))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModuleConstantMetadata {
+    pub name: String,
+    pub ty: String,
+    value: Vec<u8>,
+    documentation: Vec<String>,
+}
+
+impl ModuleConstantMetadata {
+    /// SCALE encoded value of the constant.
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ModuleWithCalls {
     index: u8,
-    calls: HashMap<String, u8>,
+    name: String,
+    calls: HashMap<String, ModuleCallMetadata>,
 }
 
 impl ModuleWithCalls {
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the calls in the order of their indices.
+    pub fn calls(&self) -> impl Iterator<Item = &ModuleCallMetadata> {
+        let mut calls = self.calls.values().collect::<Vec<_>>();
+        calls.sort_by_key(|call| call.index);
+        calls.into_iter()
+    }
+
+    pub fn call_by_name(&self, name: &str) -> Result<&ModuleCallMetadata, MetadataError> {
+        self.calls
+            .get(name)
+            .ok_or_else(|| MetadataError::CallNotFound(name.into()))
+    }
+
+    pub fn call_by_index(&self, index: u8) -> Result<&ModuleCallMetadata, MetadataError> {
+        self.calls
+            .values()
+            .find(|call| call.index == index)
+            .ok_or(MetadataError::CallIndexNotFound(index))
+    }
+
     pub fn call<T: Encode>(
         &self,
         function: &'static str,
@@ -170,13 +311,43 @@ impl ModuleWithCalls {
         let fn_index = self
             .calls
             .get(function)
-            .ok_or(MetadataError::CallNotFound(function))?;
-        let mut bytes = vec![self.index, *fn_index];
+            .ok_or_else(|| MetadataError::CallNotFound(function.into()))?
+            .index;
+        let mut bytes = vec![self.index, fn_index];
         bytes.extend(params.encode());
         Ok(Encoded(bytes))
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct ModuleCallMetadata {
+    index: u8,
+    pub name: String,
+    arguments: Vec<CallArgument>,
+    documentation: Vec<String>,
+}
+
+impl ModuleCallMetadata {
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    pub fn arguments(&self) -> &[CallArgument] {
+        &self.arguments
+    }
+
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
+}
+
+/// Name and type of a call argument, e.g., `dest: <T::Lookup as StaticLookup>::Source`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CallArgument {
+    pub name: String,
+    pub ty: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct ModuleWithEvents {
     index: u8,
@@ -185,12 +356,19 @@ pub struct ModuleWithEvents {
 }
 
 impl ModuleWithEvents {
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Returns the events in the order of their indices.
     pub fn events(&self) -> impl Iterator<Item = &ModuleEventMetadata> {
-        self.events.values()
+        let mut events = self.events.values().collect::<Vec<_>>();
+        events.sort_by_key(|event| event.index);
+        events.into_iter()
     }
 
     pub fn event(&self, index: u8) -> Result<&ModuleEventMetadata, MetadataError> {
@@ -198,34 +376,62 @@ impl ModuleWithEvents {
             .get(&index)
             .ok_or(MetadataError::EventNotFound(index))
     }
+
+    pub fn event_by_name(&self, name: &str) -> Result<&ModuleEventMetadata, MetadataError> {
+        self.events
+            .values()
+            .find(|event| event.name == name)
+            .ok_or_else(|| MetadataError::EventNameNotFound(name.into()))
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ModuleWithErrors {
     index: u8,
     name: String,
-    errors: HashMap<u8, String>,
+    errors: HashMap<u8, ModuleErrorMetadata>,
 }
 
 impl ModuleWithErrors {
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    pub fn error(&self, index: u8) -> Result<&String, MetadataError> {
+    /// Returns the errors in the order of their indices.
+    pub fn errors(&self) -> impl Iterator<Item = &ModuleErrorMetadata> {
+        let mut errors = self.errors.values().collect::<Vec<_>>();
+        errors.sort_by_key(|error| error.index);
+        errors.into_iter()
+    }
+
+    pub fn error(&self, index: u8) -> Result<&ModuleErrorMetadata, MetadataError> {
         self.errors
             .get(&index)
             .ok_or(MetadataError::ErrorNotFound(index))
     }
+
+    pub fn error_by_name(&self, name: &str) -> Result<&ModuleErrorMetadata, MetadataError> {
+        self.errors
+            .values()
+            .find(|error| error.name == name)
+            .ok_or_else(|| MetadataError::ErrorNameNotFound(name.into()))
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct StorageMetadata {
     pub module_prefix: String,
     pub storage_prefix: String,
     modifier: StorageEntryModifier,
     pub ty: StorageEntryType,
+    #[serde(serialize_with = "crate::value::serialize_hex")]
     default: Vec<u8>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    documentation: Vec<String>,
 }
 
 impl StorageMetadata {
@@ -239,6 +445,31 @@ impl StorageMetadata {
         Decode::decode(&mut &self.default[..]).map_err(MetadataError::DefaultError)
     }
 
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
+
+    /// Returns the hashers and the types of the keys, none for a plain storage.
+    pub fn key_types(&self) -> Vec<(&StorageHasher, &str)> {
+        fn decoded<'a>(ty: &'a DecodeDifferent<&'static str, String>) -> &'a str {
+            match ty {
+                DecodeDifferent::Encode(ty) => ty,
+                DecodeDifferent::Decoded(ty) => ty,
+            }
+        }
+        match &self.ty {
+            StorageEntryType::Plain(_) => Vec::new(),
+            StorageEntryType::Map { hasher, key, .. } => vec![(hasher, decoded(key))],
+            StorageEntryType::DoubleMap {
+                hasher,
+                key1,
+                key2,
+                key2_hasher,
+                ..
+            } => vec![(hasher, decoded(key1)), (key2_hasher, decoded(key2))],
+        }
+    }
+
     pub fn hash(hasher: &StorageHasher, bytes: &[u8]) -> Vec<u8> {
         match hasher {
             StorageHasher::Identity => bytes.to_vec(),
@@ -350,14 +581,41 @@ impl<K1: Encode, K2: Encode> StorageDoubleMap<K1, K2> {
 
 #[derive(Clone, Debug)]
 pub struct ModuleEventMetadata {
+    index: u8,
     pub name: String,
     arguments: Vec<EventArg>,
+    documentation: Vec<String>,
 }
 
 impl ModuleEventMetadata {
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
     pub fn arguments(&self) -> Vec<EventArg> {
         self.arguments.to_vec()
     }
+
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModuleErrorMetadata {
+    index: u8,
+    pub name: String,
+    documentation: Vec<String>,
+}
+
+impl ModuleErrorMetadata {
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
 }
 
 /// Naive representation of event argument types, supports current set of substrate EventArg types.
@@ -405,6 +663,25 @@ impl FromStr for EventArg {
     }
 }
 
+impl fmt::Display for EventArg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventArg::Primitive(p) => write!(f, "{}", p),
+            EventArg::Vec(arg) => write!(f, "Vec<{}>", arg),
+            EventArg::Tuple(args) => {
+                write!(f, "(")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
 impl EventArg {
     /// Returns all primitive types for this EventArg
     pub fn primitives(&self) -> Vec<String> {
@@ -463,11 +740,17 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
                     storage_map.insert(storage_prefix, entry);
                 }
             }
+            let mut constant_map = HashMap::new();
+            for constant in convert(module.constants)?.into_iter() {
+                let constant = convert_constant(constant)?;
+                constant_map.insert(constant.name.clone(), constant);
+            }
             modules.insert(
                 module_name.clone(),
                 ModuleMetadata {
                     name: module_name.clone(),
                     storage: storage_map,
+                    constants: constant_map,
                 },
             );
 
@@ -475,12 +758,21 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
                 let mut call_map = HashMap::new();
                 for (index, call) in convert(calls)?.into_iter().enumerate() {
                     let name = convert(call.name)?;
-                    call_map.insert(name, index as u8);
+                    call_map.insert(
+                        name.clone(),
+                        ModuleCallMetadata {
+                            index: index as u8,
+                            name,
+                            arguments: convert_call_arguments(call.arguments)?,
+                            documentation: convert(call.documentation)?,
+                        },
+                    );
                 }
                 modules_with_calls.insert(
                     module_name.clone(),
                     ModuleWithCalls {
                         index: modules_with_calls.len() as u8,
+                        name: module_name.clone(),
                         calls: call_map,
                     },
                 );
@@ -488,7 +780,7 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
             if let Some(events) = module.event {
                 let mut event_map = HashMap::new();
                 for (index, event) in convert(events)?.into_iter().enumerate() {
-                    event_map.insert(index as u8, convert_event(event)?);
+                    event_map.insert(index as u8, convert_event(index as u8, event)?);
                 }
                 modules_with_events.insert(
                     module_name.clone(),
@@ -501,7 +793,7 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
             }
             let mut error_map = HashMap::new();
             for (index, error) in convert(module.errors)?.into_iter().enumerate() {
-                error_map.insert(index as u8, convert_error(error)?);
+                error_map.insert(index as u8, convert_error(index as u8, error)?);
             }
             modules_with_errors.insert(
                 module_name.clone(),
@@ -529,6 +821,7 @@ fn convert<B: 'static, O: 'static>(dd: DecodeDifferent<B, O>) -> Result<O, Conve
 }
 
 fn convert_event(
+    index: u8,
     event: frame_metadata::EventMetadata,
 ) -> Result<ModuleEventMetadata, ConversionError> {
     let name = convert(event.name)?;
@@ -537,7 +830,26 @@ fn convert_event(
         let arg = arg.parse::<EventArg>()?;
         arguments.push(arg);
     }
-    Ok(ModuleEventMetadata { name, arguments })
+    Ok(ModuleEventMetadata {
+        index,
+        name,
+        arguments,
+        documentation: convert(event.documentation)?,
+    })
+}
+
+fn convert_call_arguments(
+    arguments: frame_metadata::DecodeDifferentArray<frame_metadata::FunctionArgumentMetadata>,
+) -> Result<Vec<CallArgument>, ConversionError> {
+    convert(arguments)?
+        .into_iter()
+        .map(|arg| {
+            Ok(CallArgument {
+                name: convert(arg.name)?,
+                ty: convert(arg.ty)?,
+            })
+        })
+        .collect()
 }
 
 fn convert_entry(
@@ -552,9 +864,135 @@ fn convert_entry(
         modifier: entry.modifier,
         ty: entry.ty,
         default,
+        documentation: convert(entry.documentation)?,
     })
 }
 
-fn convert_error(error: frame_metadata::ErrorMetadata) -> Result<String, ConversionError> {
-    convert(error.name)
+fn convert_constant(
+    constant: frame_metadata::ModuleConstantMetadata,
+) -> Result<ModuleConstantMetadata, ConversionError> {
+    Ok(ModuleConstantMetadata {
+        name: convert(constant.name)?,
+        ty: convert(constant.ty)?,
+        value: convert(constant.value)?,
+        documentation: convert(constant.documentation)?,
+    })
+}
+
+fn convert_error(
+    index: u8,
+    error: frame_metadata::ErrorMetadata,
+) -> Result<ModuleErrorMetadata, ConversionError> {
+    Ok(ModuleErrorMetadata {
+        index,
+        name: convert(error.name)?,
+        documentation: convert(error.documentation)?,
+    })
+}
+
+/// Path of the metadata used by the tests.
+#[cfg(test)]
+pub(crate) const TEST_METADATA_PATH: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/metadata.txt");
+
+/// Loads the metadata used by the tests.
+#[cfg(test)]
+pub(crate) fn test_metadata() -> Metadata {
+    Metadata::load(TEST_METADATA_PATH).expect("failed to load metadata")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA_HEX: &str = include_str!("../test_data/metadata.txt");
+
+    #[test]
+    fn load_metadata_in_all_formats_should_work() {
+        let hex_str = METADATA_HEX.trim();
+        let scale = hex::decode(hex_str).unwrap();
+
+        let from_scale = Metadata::from_bytes(&scale).unwrap();
+        let from_hex = Metadata::from_bytes(hex_str.as_bytes()).unwrap();
+        let from_prefixed_hex =
+            Metadata::from_bytes(format!("0x{}\n", hex_str).as_bytes()).unwrap();
+        let rpc_response = format!(r#"{{"jsonrpc":"2.0","result":"0x{}","id":1}}"#, hex_str);
+        let from_rpc_response = Metadata::from_bytes(rpc_response.as_bytes()).unwrap();
+
+        for metadata in &[from_hex, from_prefixed_hex, from_rpc_response] {
+            assert_eq!(metadata.modules.len(), from_scale.modules.len());
+        }
+        assert!(Metadata::load(TEST_METADATA_PATH).is_ok());
+    }
+
+    #[test]
+    fn load_invalid_metadata_should_fail() {
+        assert!(matches!(
+            Metadata::from_bytes(b"0xzz"),
+            Err(LoadError::InvalidHex(_))
+        ));
+        assert!(matches!(
+            Metadata::from_bytes(br#"{"jsonrpc":"2.0","error":{"code":-32601},"id":1}"#),
+            Err(LoadError::RpcError(_))
+        ));
+        assert!(matches!(
+            Metadata::from_bytes(br#"{"jsonrpc":"2.0","result":null,"id":1}"#),
+            Err(LoadError::MissingResult)
+        ));
+        assert!(matches!(
+            Metadata::from_bytes(br#"{"jsonrpc":"#),
+            Err(LoadError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            Metadata::from_bytes(b"6d657461ff"),
+            Err(LoadError::Decode(_))
+        ));
+        assert!(matches!(
+            Metadata::load("/non/existent/metadata"),
+            Err(LoadError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn lookup_by_name_should_work() {
+        let metadata = test_metadata();
+
+        let calls = metadata.module_with_calls("Balances").unwrap();
+        let transfer = calls.call_by_name("transfer").unwrap();
+        assert_eq!(
+            calls.call_by_index(transfer.index()).unwrap().name,
+            "transfer"
+        );
+        assert!(calls
+            .calls()
+            .enumerate()
+            .all(|(index, call)| call.index() as usize == index));
+
+        let events = &metadata.modules_with_events["Balances"];
+        let transfer = events.event_by_name("Transfer").unwrap();
+        assert_eq!(events.event(transfer.index()).unwrap().name, "Transfer");
+        assert!(events
+            .events()
+            .enumerate()
+            .all(|(index, event)| event.index() as usize == index));
+        assert!(matches!(
+            events.event_by_name("Unknown"),
+            Err(MetadataError::EventNameNotFound(_))
+        ));
+
+        let errors = &metadata.modules_with_errors["Balances"];
+        let error = errors.error_by_name("InsufficientBalance").unwrap();
+        assert_eq!(
+            errors.error(error.index()).unwrap().name,
+            "InsufficientBalance"
+        );
+        assert!(errors
+            .errors()
+            .enumerate()
+            .all(|(index, error)| error.index() as usize == index));
+        assert!(matches!(
+            errors.error_by_name("Unknown"),
+            Err(MetadataError::ErrorNameNotFound(_))
+        ));
+    }
 }
@@ -0,0 +1,117 @@
+//! Reads the state of a stopped node from its ParityDB database, i.e.,
+//! `<base-path>/chains/<chain>/paritydb/full`.
+
+use std::path::Path;
+
+use hash_db::Prefix;
+use parity_db::{Db, Options};
+use sp_core::{Blake2Hasher, H256};
+
+use crate::state_source::StateSourceError;
+use crate::trie_state::{columns, NodeDatabase, TrieState};
+
+/// STATE column of the database.
+const STATE: u8 = columns::STATE as u8;
+
+/// Trie nodes in the STATE column.
+///
+/// The STATE column is reference counted and keyed by the node hash alone since the
+/// trie nodes are not prefixed when the database supports reference counting, but the
+/// databases created before that use the prefixed node hash like RocksDB.
+pub struct ParityDbNodes(Db);
+
+impl ParityDbNodes {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StateSourceError> {
+        self.0
+            .get(STATE, key)
+            .map_err(|e| StateSourceError::Database(e.to_string()))
+    }
+}
+
+impl NodeDatabase for ParityDbNodes {
+    fn get_node(&self, hash: &H256, prefix: Prefix) -> Result<Option<Vec<u8>>, StateSourceError> {
+        match self.get(hash.as_bytes())? {
+            Some(node) => Ok(Some(node)),
+            None => self.get(&sp_trie::prefixed_key::<Blake2Hasher>(hash, prefix)),
+        }
+    }
+}
+
+/// State in the ParityDB database.
+pub type ParityDbState = TrieState<ParityDbNodes>;
+
+/// Returns the column options of the database, which have to match the ones it was created
+/// with, see `sc_client_db::parity_db::open`.
+fn options(path: &Path) -> Options {
+    let mut options = Options::with_columns(path, columns::NUM_COLUMNS as u8);
+    let state = &mut options.columns[STATE as usize];
+    state.ref_counted = true;
+    state.preimage = true;
+    state.uniform = true;
+    options
+}
+
+/// Opens the ParityDB database at `path` and returns the state at `state_root`.
+///
+/// parity-db 0.1 has no read-only mode, the database is never written to, and it is not
+/// opened unless it exists, since opening a missing database creates an empty one.
+pub fn open(path: &Path, state_root: H256) -> Result<ParityDbState, StateSourceError> {
+    if !path.join("metadata").is_file() {
+        return Err(StateSourceError::Database(format!(
+            "No ParityDB database at {}",
+            path.display()
+        )));
+    }
+    let db = Db::open(&options(path)).map_err(|e| StateSourceError::Database(e.to_string()))?;
+    Ok(TrieState::new(ParityDbNodes(db), state_root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_source::StateSource;
+    use sp_trie::{Layout, MemoryDB, TrieDBMut, TrieMut};
+
+    #[test]
+    fn open_paritydb_should_work() {
+        let path = std::env::temp_dir().join(format!("paritydb-{}", std::process::id()));
+        assert!(matches!(
+            open(&path, H256::default()),
+            Err(StateSourceError::Database(_))
+        ));
+        assert!(!path.exists());
+
+        let mut nodes = MemoryDB::<Blake2Hasher>::default();
+        let mut root = H256::default();
+        {
+            let mut trie = TrieDBMut::<Layout<Blake2Hasher>>::new(&mut nodes, &mut root);
+            trie.insert(b":code", b"\0asm").unwrap();
+            trie.insert(b":heappages", &8u64.to_le_bytes()).unwrap();
+        }
+        let db = Db::open(&options(&path)).unwrap();
+        db.commit(
+            nodes
+                .drain()
+                .into_iter()
+                .map(|(_, (node, _))| (STATE, sp_core::blake2_256(&node).to_vec(), Some(node))),
+        )
+        .unwrap();
+        let state = TrieState::new(ParityDbNodes(db), root);
+        assert_eq!(
+            state.top_pairs().unwrap(),
+            vec![
+                (hex::encode(b":code"), Some(b"\0asm".to_vec())),
+                (
+                    hex::encode(b":heappages"),
+                    Some(8u64.to_le_bytes().to_vec())
+                ),
+            ]
+        );
+        drop(state);
+
+        // The column options have to match the ones the database was created with.
+        assert!(Db::open(&Options::with_columns(&path, columns::NUM_COLUMNS as u8)).is_err());
+        assert!(open(&path, root).is_ok());
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+//! Reads the state of a stopped node from its RocksDB database, i.e.,
+//! `<base-path>/chains/<chain>/db/full`.
+
+use std::{env, fs, path::Path, process};
+
+use codec::{Compact, Decode};
+use hash_db::Prefix;
+use kvdb_rocksdb::{Database, DatabaseConfig};
+use sp_core::{Blake2Hasher, H256};
+
+use crate::state_source::StateSourceError;
+use crate::trie_state::{columns, NodeDatabase, TrieState};
+
+/// Key of the best block lookup key in the META column.
+const BEST_BLOCK: &[u8] = b"best";
+
+/// Trie nodes in the STATE column, keyed by the prefixed node hash.
+pub struct RocksDbNodes(Database);
+
+impl NodeDatabase for RocksDbNodes {
+    fn get_node(&self, hash: &H256, prefix: Prefix) -> Result<Option<Vec<u8>>, StateSourceError> {
+        let key = sp_trie::prefixed_key::<Blake2Hasher>(hash, prefix);
+        Ok(self.0.get(columns::STATE, &key)?)
+    }
+}
+
+/// State of a block in the RocksDB database.
+pub type RocksDbState = TrieState<RocksDbNodes>;
+
+/// Opens the RocksDB database at `path` and returns the state at `block`, or at the best
+/// block if `None`.
+///
+/// The state of an old block is unavailable unless the node is an archive node.
+///
+/// The database is opened read-only as a RocksDB secondary instance, whose info logs are kept
+/// in a temporary directory. It is not opened unless it exists, since kvdb-rocksdb creates a
+/// missing database.
+pub fn open(path: &Path, block: Option<H256>) -> Result<RocksDbState, StateSourceError> {
+    if !path.join("CURRENT").is_file() {
+        return Err(StateSourceError::Database(format!(
+            "No RocksDB database at {}",
+            path.display()
+        )));
+    }
+    let secondary = env::temp_dir().join(format!("rocksdb-secondary-{}", process::id()));
+    fs::create_dir_all(&secondary)?;
+    let mut config = DatabaseConfig::with_columns(columns::NUM_COLUMNS);
+    config.secondary = Some(secondary.to_string_lossy().into_owned());
+    let db = Database::open(&config, &path.to_string_lossy())?;
+
+    let lookup_key = match block {
+        Some(hash) => db
+            .get(columns::KEY_LOOKUP, hash.as_bytes())?
+            .ok_or_else(|| StateSourceError::UnknownBlock(format!("{:?}", hash)))?,
+        None => db
+            .get(columns::META, BEST_BLOCK)?
+            .ok_or_else(|| StateSourceError::UnknownBlock("best".into()))?,
+    };
+    let header = db
+        .get(columns::HEADER, &lookup_key)?
+        .ok_or_else(|| StateSourceError::UnknownBlock(format!("0x{}", hex::encode(&lookup_key))))?;
+
+    // `parent_hash ++ number ++ state_root ++ ...`
+    let (_parent_hash, _number, state_root) =
+        <(H256, Compact<u32>, H256)>::decode(&mut header.as_slice())
+            .map_err(StateSourceError::InvalidHeader)?;
+
+    Ok(TrieState::new(RocksDbNodes(db), state_root))
+}
@@ -0,0 +1,344 @@
+//! Reads the state from a running node via JSON-RPC over HTTP.
+
+use std::cell::Cell;
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value as JsonValue};
+
+use crate::metadata::{LoadError, Metadata};
+use crate::state_source::{ChildTriple, StateSource, StateSourceError, StoragePair};
+use crate::strip_hex_prefix;
+use crate::well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX;
+
+/// Default number of keys per `state_getKeysPaged` request.
+pub const DEFAULT_PAGE_SIZE: u32 = 1000;
+
+/// JSON-RPC error.
+#[derive(Debug, thiserror::Error)]
+pub enum RpcError {
+    /// Failed to send the request or read the response.
+    #[error("HTTP error: {0}")]
+    Http(String),
+    /// Unexpected `result` of the response.
+    #[error("Invalid JSON-RPC response: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    /// The response is an error object.
+    #[error("JSON-RPC error response: {0}")]
+    Rpc(String),
+    /// Storage value is not valid hex.
+    #[error("Invalid hex {0}: {1}")]
+    InvalidHex(String, hex::FromHexError),
+    #[error(transparent)]
+    Metadata(#[from] LoadError),
+}
+
+/// Blocking JSON-RPC client.
+pub struct RpcClient {
+    url: String,
+    next_id: Cell<u64>,
+}
+
+impl RpcClient {
+    /// Creates a client of the node HTTP endpoint, e.g., `http://localhost:9933`.
+    pub fn new<S: Into<String>>(url: S) -> Self {
+        Self {
+            url: url.into(),
+            next_id: Cell::new(1),
+        }
+    }
+
+    fn request<T: DeserializeOwned>(&self, method: &str, params: JsonValue) -> Result<T, RpcError> {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+
+        let response: JsonValue = attohttpc::post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            }))
+            .and_then(|request| request.send())
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .map_err(|e| RpcError::Http(e.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(RpcError::Rpc(error.to_string()));
+        }
+        let result = response.get("result").cloned().unwrap_or(JsonValue::Null);
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Returns the metadata at block `at`, or the best block if `None`.
+    pub fn metadata(&self, at: Option<&str>) -> Result<Metadata, RpcError> {
+        let metadata: String = self.request("state_getMetadata", json!([at]))?;
+        Ok(Metadata::from_bytes(metadata.as_bytes())?)
+    }
+
+    /// Returns at most `count` keys starting with `prefix` after `start_key`, the keys are
+    /// in hex with `0x`.
+    pub fn keys_paged(
+        &self,
+        prefix: &str,
+        count: u32,
+        start_key: Option<&str>,
+        at: Option<&str>,
+    ) -> Result<Vec<String>, RpcError> {
+        self.request("state_getKeysPaged", json!([prefix, count, start_key, at]))
+    }
+
+    /// Returns the value of storage `key` in hex.
+    pub fn storage(&self, key: &str, at: Option<&str>) -> Result<Option<Vec<u8>>, RpcError> {
+        let value: Option<String> = self.request("state_getStorage", json!([key, at]))?;
+        decode_value(value)
+    }
+
+    /// Returns the keys starting with `prefix` of the child trie `child_info`, i.e.,
+    /// `:child_storage:default:` followed by the child trie id, in hex with `0x`.
+    pub fn child_keys(
+        &self,
+        child_info: &str,
+        prefix: &str,
+        at: Option<&str>,
+    ) -> Result<Vec<String>, RpcError> {
+        self.request("childstate_getKeys", json!([child_info, prefix, at]))
+    }
+
+    /// Returns the value of `key` in the child trie `child_info`.
+    pub fn child_storage(
+        &self,
+        child_info: &str,
+        key: &str,
+        at: Option<&str>,
+    ) -> Result<Option<Vec<u8>>, RpcError> {
+        let value: Option<String> =
+            self.request("childstate_getStorage", json!([child_info, key, at]))?;
+        decode_value(value)
+    }
+}
+
+fn decode_hex(s: String) -> Result<Vec<u8>, RpcError> {
+    hex::decode(strip_hex_prefix(&s)).map_err(|e| RpcError::InvalidHex(s, e))
+}
+
+fn decode_value(value: Option<String>) -> Result<Option<Vec<u8>>, RpcError> {
+    value.map(decode_hex).transpose()
+}
+
+/// State of the block `at` of the node, limited to the keys starting with `prefix`.
+pub struct RpcState {
+    pub client: RpcClient,
+    /// Block hash in hex, the best block if `None`.
+    pub at: Option<String>,
+    /// Storage key prefix in hex, e.g., `twox128(pallet) ++ twox128(item)`.
+    pub prefix: String,
+    pub page_size: u32,
+}
+
+impl RpcState {
+    pub fn new(client: RpcClient, at: Option<String>) -> Self {
+        Self {
+            client,
+            at,
+            prefix: String::new(),
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+
+    /// Returns all the keys starting with `prefix` in hex.
+    pub fn keys(&self, prefix: &str) -> Result<Vec<String>, RpcError> {
+        let prefix = format!("0x{}", strip_hex_prefix(prefix));
+        let mut keys: Vec<String> = Vec::new();
+        loop {
+            let page = self.client.keys_paged(
+                &prefix,
+                self.page_size,
+                keys.last().map(String::as_str),
+                self.at.as_deref(),
+            )?;
+            let done = page.len() < self.page_size as usize;
+            keys.extend(page);
+            if done {
+                return Ok(keys);
+            }
+        }
+    }
+
+    /// Returns the triples of the default child tries, unless `self.prefix` excludes their
+    /// roots in the top trie.
+    fn triples(&self) -> Result<Vec<ChildTriple>, RpcError> {
+        let child_prefix = hex::encode(DEFAULT_CHILD_STORAGE_KEY_PREFIX);
+        let prefix = strip_hex_prefix(&self.prefix).to_lowercase();
+        if !child_prefix.starts_with(&prefix) && !prefix.starts_with(&child_prefix) {
+            return Ok(Vec::new());
+        }
+
+        let roots = if prefix.len() > child_prefix.len() {
+            &prefix
+        } else {
+            &child_prefix
+        };
+
+        let at = self.at.as_deref();
+        let mut triples = Vec::new();
+        for child_info in self.keys(roots)? {
+            for key in self.client.child_keys(&child_info, "0x", at)? {
+                if let Some(value) = self.client.child_storage(&child_info, &key, at)? {
+                    triples.push((decode_hex(child_info.clone())?, decode_hex(key)?, value));
+                }
+            }
+        }
+        Ok(triples)
+    }
+
+    /// Returns the key/value pairs of the keys starting with `prefix` in hex.
+    fn pairs(&self, prefix: &str) -> Result<Vec<StoragePair>, RpcError> {
+        self.keys(prefix)?
+            .into_iter()
+            .map(|key| {
+                let value = self.client.storage(&key, self.at.as_deref())?;
+                Ok((strip_hex_prefix(&key).to_lowercase(), value))
+            })
+            .collect()
+    }
+}
+
+impl StateSource for RpcState {
+    fn top_pairs(&self) -> Result<Vec<StoragePair>, StateSourceError> {
+        Ok(self.pairs(&self.prefix)?)
+    }
+
+    fn prefix_pairs(&self, prefix: &[u8]) -> Result<Vec<StoragePair>, StateSourceError> {
+        Ok(self.pairs(&hex::encode(prefix))?)
+    }
+
+    fn child_triples(&self) -> Result<Vec<ChildTriple>, StateSourceError> {
+        Ok(self.triples()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::TEST_METADATA_PATH;
+    use std::{
+        collections::BTreeMap,
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+
+    /// Serves the JSON-RPC requests of `storage` and the child tries `children` on a local
+    /// port, one request per connection.
+    fn mock_server(
+        storage: BTreeMap<String, String>,
+        children: BTreeMap<String, BTreeMap<String, String>>,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let metadata = std::fs::read_to_string(TEST_METADATA_PATH).unwrap();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim().to_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(length) = line.strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let request: JsonValue = serde_json::from_slice(&body).unwrap();
+
+                let params = &request["params"];
+                let result = match request["method"].as_str().unwrap() {
+                    "state_getMetadata" => json!(metadata.trim()),
+                    "state_getKeysPaged" => {
+                        let prefix = params[0].as_str().unwrap();
+                        let count = params[1].as_u64().unwrap() as usize;
+                        let start_key = params[2].as_str().unwrap_or("");
+                        json!(storage
+                            .keys()
+                            .filter(|key| key.starts_with(prefix) && key.as_str() > start_key)
+                            .take(count)
+                            .collect::<Vec<_>>())
+                    }
+                    "state_getStorage" => json!(storage.get(params[0].as_str().unwrap())),
+                    "childstate_getKeys" => {
+                        let prefix = params[1].as_str().unwrap();
+                        json!(children[params[0].as_str().unwrap()]
+                            .keys()
+                            .filter(|key| key.starts_with(prefix))
+                            .collect::<Vec<_>>())
+                    }
+                    "childstate_getStorage" => {
+                        json!(children[params[0].as_str().unwrap()].get(params[1].as_str().unwrap()))
+                    }
+                    method => panic!("Unexpected method {}", method),
+                };
+                let body =
+                    json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        url
+    }
+
+    #[test]
+    fn rpc_state_should_work() {
+        let account_prefix = "0x26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9";
+        let mut storage = BTreeMap::new();
+        for i in 0..3 {
+            storage.insert(format!("{}{:064x}", account_prefix, i), format!("0x0{}", i));
+        }
+        storage.insert("0x3a636f6465".into(), "0x0061736d".into());
+        let child_info = format!("0x{}", hex::encode(b":child_storage:default:id"));
+        storage.insert(child_info.clone(), format!("0x{}", hex::encode([0u8; 32])));
+        let mut children = BTreeMap::new();
+        children.insert(
+            child_info,
+            vec![("0x01".to_string(), "0x02".to_string())]
+                .into_iter()
+                .collect(),
+        );
+
+        let client = RpcClient::new(mock_server(storage, children));
+        assert!(client.metadata(None).unwrap().module("System").is_ok());
+        assert_eq!(
+            client.storage("0x3a636f6465", None).unwrap(),
+            Some(b"\0asm".to_vec())
+        );
+        assert_eq!(client.storage("0x00", None).unwrap(), None);
+
+        let mut state = RpcState::new(client, None);
+        state.prefix = account_prefix.into();
+        state.page_size = 2;
+        let pairs = state.top_pairs().unwrap();
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs[2].0, format!("{}{:064x}", &account_prefix[2..], 2));
+        assert_eq!(pairs[2].1, Some(vec![2]));
+        // The prefix excludes the child tries.
+        assert_eq!(state.child_triples().unwrap(), Vec::new());
+
+        state.prefix = String::new();
+        assert_eq!(
+            state.child_triples().unwrap(),
+            vec![(b":child_storage:default:id".to_vec(), vec![1], vec![2])]
+        );
+    }
+}
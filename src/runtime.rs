@@ -0,0 +1,85 @@
+//! Extracts the metadata by executing the `Metadata_metadata` runtime API of a WASM blob.
+
+use std::{convert::TryFrom, path::Path};
+
+use codec::Decode;
+use frame_metadata::RuntimeMetadataPrefixed;
+use sc_executor::{WasmExecutionMethod, WasmExecutor};
+use sp_core::traits::{CallInWasm, MissingHostFunctions};
+use sp_io::TestExternalities;
+use sp_wasm_interface::HostFunctions;
+
+use crate::metadata::{Metadata, MetadataError};
+
+/// Same as the default heap pages of sc-executor.
+const DEFAULT_HEAP_PAGES: u64 = 2048;
+
+/// Magic number of a WASM binary.
+pub const WASM_MAGIC: &[u8] = b"\0asm";
+
+/// Runtime error.
+#[derive(Debug, thiserror::Error)]
+pub enum RuntimeError {
+    /// Failed to read the runtime code.
+    #[error("Failed to read runtime: {0}")]
+    Io(#[from] std::io::Error),
+    /// The code is not a WASM binary.
+    #[error("Runtime code is not a WASM binary")]
+    NotWasm,
+    /// Failed to execute the runtime API.
+    #[error("Failed to call Metadata_metadata: {0}")]
+    Executor(String),
+    /// Failed to decode the result of runtime API.
+    #[error("Failed to decode metadata: {0}")]
+    Decode(#[from] codec::Error),
+    /// Failed to convert the decoded metadata.
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+}
+
+/// Returns true if `code` is a WASM binary.
+pub fn is_wasm(code: &[u8]) -> bool {
+    code.starts_with(WASM_MAGIC)
+}
+
+/// Loads the runtime code from `path`, e.g., `polkadot_runtime.compact.wasm`, and extracts
+/// the metadata from it.
+pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<Metadata, RuntimeError> {
+    let code = std::fs::read(path)?;
+    metadata_from_wasm(&code)
+}
+
+/// Executes `Metadata_metadata` of the runtime `code` with the interpreted executor.
+pub fn metadata_from_wasm(code: &[u8]) -> Result<Metadata, RuntimeError> {
+    if !is_wasm(code) {
+        return Err(RuntimeError::NotWasm);
+    }
+
+    let executor = WasmExecutor::new(
+        WasmExecutionMethod::Interpreted,
+        Some(DEFAULT_HEAP_PAGES),
+        sp_io::SubstrateHostFunctions::host_functions(),
+        1,
+    );
+
+    // `Metadata_metadata` does not touch the storage, an empty externalities is enough.
+    let mut ext = TestExternalities::default();
+    let mut ext = ext.ext();
+
+    let encoded = executor
+        .call_in_wasm(
+            code,
+            None,
+            "Metadata_metadata",
+            &[],
+            &mut ext,
+            MissingHostFunctions::Allow,
+        )
+        .map_err(RuntimeError::Executor)?;
+
+    // The runtime API returns `OpaqueMetadata`, i.e., `Vec<u8>` of the encoded metadata.
+    let opaque: Vec<u8> = Decode::decode(&mut encoded.as_slice())?;
+    let prefixed = RuntimeMetadataPrefixed::decode(&mut opaque.as_slice())?;
+
+    Ok(Metadata::try_from(prefixed)?)
+}
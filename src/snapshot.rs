@@ -0,0 +1,159 @@
+//! State snapshot produced by `try-runtime`, i.e., the `.snap` file.
+//!
+//! The snapshot is SCALE encoded as `(StateVersion, Vec<KeyValue>, Vec<(ChildInfo, Vec<KeyValue>)>)`,
+//! the snapshots of the earlier `try-runtime` only consist of `Vec<KeyValue>` of the top trie.
+
+use std::path::Path;
+
+use codec::{Decode, DecodeAll, Input};
+
+use crate::state_source::{ChildTriple, StateSource, StateSourceError, StoragePair};
+
+/// Snapshot error.
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    /// Failed to read the snapshot file.
+    #[error("Failed to read snapshot: {0}")]
+    Io(#[from] std::io::Error),
+    /// Failed to decode the snapshot.
+    #[error("Invalid snapshot: {0}")]
+    Decode(#[from] codec::Error),
+}
+
+/// Storage key/value pair.
+pub type KeyValue = (Vec<u8>, Vec<u8>);
+
+/// Version of the state trie layout, see `sp_core::storage::StateVersion`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StateVersion {
+    V0,
+    /// The values longer than 32 bytes are hashed in the trie nodes.
+    V1,
+}
+
+impl Decode for StateVersion {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        match input.read_byte()? {
+            0 => Ok(Self::V0),
+            1 => Ok(Self::V1),
+            _ => Err("Invalid state version".into()),
+        }
+    }
+}
+
+/// Decoded `try-runtime` snapshot.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Snapshot {
+    /// `None` for the snapshots without the state version.
+    pub state_version: Option<StateVersion>,
+    pub top: Vec<KeyValue>,
+    /// Key/value pairs of the default child tries, keyed by the child trie id.
+    pub child: Vec<(Vec<u8>, Vec<KeyValue>)>,
+}
+
+/// `sp_core::storage::ChildInfo`, the only variant is the default child trie.
+fn decode_child_info<I: Input>(input: &mut I) -> Result<Vec<u8>, codec::Error> {
+    match input.read_byte()? {
+        0 => Vec::<u8>::decode(input),
+        _ => Err("Invalid child info".into()),
+    }
+}
+
+fn decode_versioned(mut bytes: &[u8]) -> Result<Snapshot, codec::Error> {
+    let input = &mut bytes;
+    let state_version = StateVersion::decode(input)?;
+    let top = Vec::<KeyValue>::decode(input)?;
+    let children = codec::Compact::<u32>::decode(input)?.0;
+    let mut child = Vec::new();
+    for _ in 0..children {
+        child.push((decode_child_info(input)?, Vec::<KeyValue>::decode(input)?));
+    }
+    if !input.is_empty() {
+        return Err("Trailing bytes".into());
+    }
+    Ok(Snapshot {
+        state_version: Some(state_version),
+        top,
+        child,
+    })
+}
+
+impl Snapshot {
+    /// Loads the snapshot file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Decodes the snapshot, falling back to the snapshot without the state version.
+    ///
+    /// Both formats have to consume all the bytes, the error of the versioned format is
+    /// returned if neither of them matches.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let versioned_err = match decode_versioned(bytes) {
+            Ok(snapshot) => return Ok(snapshot),
+            Err(e) => e,
+        };
+        let top = <Vec<KeyValue>>::decode_all(bytes).map_err(|_| versioned_err)?;
+        Ok(Self {
+            state_version: None,
+            top,
+            child: Vec::new(),
+        })
+    }
+}
+
+impl StateSource for Snapshot {
+    fn top_pairs(&self) -> Result<Vec<StoragePair>, StateSourceError> {
+        Ok(self
+            .top
+            .iter()
+            .map(|(key, value)| (hex::encode(key), Some(value.clone())))
+            .collect())
+    }
+
+    fn child_triples(&self) -> Result<Vec<ChildTriple>, StateSourceError> {
+        Ok(self
+            .child
+            .iter()
+            .flat_map(|(child_trie_id, pairs)| {
+                pairs
+                    .iter()
+                    .map(move |(key, value)| (child_trie_id.clone(), key.clone(), value.clone()))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::Encode;
+
+    #[test]
+    fn decode_snapshot_should_work() {
+        let top = vec![(b":code".to_vec(), b"\0asm".to_vec())];
+        let child = vec![((0u8, b"id".to_vec()), vec![(vec![1u8], vec![2u8])])];
+        let snapshot = Snapshot::from_bytes(&(1u8, &top, child).encode()).unwrap();
+        assert_eq!(snapshot.state_version, Some(StateVersion::V1));
+        assert_eq!(snapshot.top, top);
+        assert_eq!(
+            snapshot.child_triples().unwrap(),
+            vec![(b"id".to_vec(), vec![1], vec![2])]
+        );
+
+        let legacy = Snapshot::from_bytes(&top.encode()).unwrap();
+        assert_eq!(legacy.state_version, None);
+        assert_eq!(
+            legacy.top_pairs().unwrap(),
+            vec![(hex::encode(b":code"), Some(b"\0asm".to_vec()))]
+        );
+
+        // A V0 snapshot with a corrupt tail, whose leading `0x00` is an empty legacy snapshot.
+        let mut corrupt = (0u8, &top, Vec::<((u8, Vec<u8>), Vec<KeyValue>)>::new()).encode();
+        corrupt.truncate(corrupt.len() - 2);
+        assert!(matches!(
+            Snapshot::from_bytes(&corrupt),
+            Err(SnapshotError::Decode(_))
+        ));
+    }
+}
@@ -0,0 +1,74 @@
+//! Sources of the state key/value pairs besides the text input.
+
+use crate::chain_spec::{ChainSpec, ChainSpecError};
+
+/// Storage key in hex without `0x` and the storage value if any.
+pub type StoragePair = (String, Option<Vec<u8>>);
+
+/// `(child_info, key, value)` of a child trie.
+pub type ChildTriple = (Vec<u8>, Vec<u8>, Vec<u8>);
+
+/// State source error.
+#[derive(Debug, thiserror::Error)]
+pub enum StateSourceError {
+    /// Failed to read the database.
+    #[error("Database error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Failed to open or read the database other than an IO error.
+    #[error("Database error: {0}")]
+    Database(String),
+    /// The block is not in the database.
+    #[error("Block {0} not found")]
+    UnknownBlock(String),
+    /// Failed to decode the block header.
+    #[error("Invalid block header: {0}")]
+    InvalidHeader(codec::Error),
+    /// Failed to walk the state trie, usually because the state has been pruned.
+    #[error("Failed to walk the state trie: {0}")]
+    Trie(String),
+    #[error(transparent)]
+    ChainSpec(#[from] ChainSpecError),
+    #[cfg(feature = "rpc")]
+    #[error(transparent)]
+    Rpc(#[from] crate::rpc::RpcError),
+}
+
+/// Source of the state key/value pairs.
+pub trait StateSource {
+    /// Returns the key/value pairs of the top trie, the key is in hex without `0x`.
+    fn top_pairs(&self) -> Result<Vec<StoragePair>, StateSourceError>;
+
+    /// Returns the key/value pairs of the top trie starting with `prefix`.
+    ///
+    /// The sources override it if they can seek to the prefix instead of walking all the keys.
+    fn prefix_pairs(&self, prefix: &[u8]) -> Result<Vec<StoragePair>, StateSourceError> {
+        let prefix = hex::encode(prefix);
+        Ok(self
+            .top_pairs()?
+            .into_iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .collect())
+    }
+
+    /// Returns the `(child_info, key, value)` triples of the default child tries.
+    fn child_triples(&self) -> Result<Vec<ChildTriple>, StateSourceError> {
+        Ok(Vec::new())
+    }
+}
+
+/// Key/value pairs of a state dump.
+impl StateSource for Vec<StoragePair> {
+    fn top_pairs(&self) -> Result<Vec<StoragePair>, StateSourceError> {
+        Ok(self.clone())
+    }
+}
+
+impl StateSource for ChainSpec {
+    fn top_pairs(&self) -> Result<Vec<StoragePair>, StateSourceError> {
+        Ok(ChainSpec::top_pairs(self)?)
+    }
+
+    fn child_triples(&self) -> Result<Vec<ChildTriple>, StateSourceError> {
+        Ok(ChainSpec::child_triples(self)?)
+    }
+}
@@ -0,0 +1,184 @@
+//! Builds the storage keys from the JSON keys by the key types and hashers in metadata.
+//!
+//! Unlike [`StorageMap::key`] and [`StorageDoubleMap::key`], the key types need not be known at
+//! compile time, e.g., the key of `System::Account` is built from an SS58 address.
+//!
+//! [`StorageMap::key`]: crate::metadata::StorageMap::key
+//! [`StorageDoubleMap::key`]: crate::metadata::StorageDoubleMap::key
+
+use serde_json::Value as JsonValue;
+use sp_core::storage::StorageKey;
+
+use crate::decode_storage_key_value::StorageMetadataLookupTable;
+use crate::decoder::ValueDecoder;
+use crate::encoder::{EncodeError, ValueEncoder};
+use crate::metadata::StorageMetadata;
+
+/// Error of building a storage key.
+#[derive(Debug, thiserror::Error)]
+pub enum StorageKeyError {
+    /// The storage item is not in the metadata.
+    #[error("Storage {0}::{1} not found")]
+    UnknownStorage(String, String),
+    /// More keys are given than the storage item has.
+    #[error("Storage {0}::{1} has {2} keys only")]
+    TooManyKeys(String, String, usize),
+    #[error(transparent)]
+    Encode(#[from] EncodeError),
+}
+
+/// Parses a key given on the command line, a JSON value or else a plain string, e.g., `42`,
+/// `"0x01000000"` and `5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY`.
+pub fn parse_key_arg(s: &str) -> JsonValue {
+    serde_json::from_str(s).unwrap_or_else(|_| JsonValue::String(s.into()))
+}
+
+/// Encodes `key` as type `ty`, a `0x` prefixed hex string which is not valid for `ty` is taken
+/// as the SCALE encoded key if it decodes as `ty`.
+fn encode_key(
+    decoder: &ValueDecoder,
+    encoder: &ValueEncoder,
+    ty: &str,
+    key: &JsonValue,
+) -> Result<Vec<u8>, EncodeError> {
+    encoder.encode(ty, key).or_else(|e| {
+        key.as_str()
+            .and_then(|s| s.strip_prefix("0x"))
+            .and_then(|s| hex::decode(s).ok())
+            .filter(|bytes| decoder.decode_all(ty, bytes).is_ok())
+            .ok_or(e)
+    })
+}
+
+/// Returns the storage key of `pallet::item` with `keys`, or the prefix of the entries with
+/// these keys if fewer keys are given, e.g., only key1 of a DoubleMap. See
+/// [`StorageMetadataLookupTable::storage_metadata`] for `pallet`.
+pub fn storage_key(
+    table: &StorageMetadataLookupTable,
+    decoder: &ValueDecoder,
+    pallet: &str,
+    item: &str,
+    keys: &[JsonValue],
+) -> Result<StorageKey, StorageKeyError> {
+    let storage = table
+        .storage_metadata(pallet, item)
+        .ok_or_else(|| StorageKeyError::UnknownStorage(pallet.into(), item.into()))?;
+    let key_types = storage.key_types();
+    if keys.len() > key_types.len() {
+        return Err(StorageKeyError::TooManyKeys(
+            pallet.into(),
+            item.into(),
+            key_types.len(),
+        ));
+    }
+
+    let encoder = ValueEncoder::new(decoder);
+    let mut bytes = storage.prefix().0;
+    for ((hasher, ty), key) in key_types.into_iter().zip(keys) {
+        bytes.extend(StorageMetadata::hash(
+            hasher,
+            &encode_key(decoder, &encoder, ty, key)?,
+        ));
+    }
+    Ok(StorageKey(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::test_metadata;
+    use crate::storage_prefix::storage_prefix;
+    use codec::Encode;
+    use serde_json::json;
+
+    #[test]
+    fn storage_key_should_work() {
+        let metadata = test_metadata();
+        let account = metadata
+            .module("System")
+            .unwrap()
+            .storage("Account")
+            .unwrap()
+            .map::<[u8; 32]>()
+            .unwrap()
+            .key(&[1; 32]);
+        let authored_blocks = metadata
+            .module("ImOnline")
+            .unwrap()
+            .storage("AuthoredBlocks")
+            .unwrap()
+            .double_map::<u32, [u8; 32]>()
+            .unwrap()
+            .key(&1, &[2; 32]);
+        let table: StorageMetadataLookupTable = metadata.into();
+        let decoder = ValueDecoder::default();
+
+        let key = |pallet, item, keys: &[JsonValue]| {
+            storage_key(&table, &decoder, pallet, item, keys).map(|key| key.0)
+        };
+        let account_id = |byte: u8| json!(format!("0x{}", hex::encode([byte; 32])));
+
+        assert_eq!(
+            key("System", "Account", &[account_id(1)]).unwrap(),
+            account.0
+        );
+        assert_eq!(
+            key("ImOnline", "AuthoredBlocks", &[json!(1), account_id(2)]).unwrap(),
+            authored_blocks.0
+        );
+        // The SCALE encoded key in hex.
+        assert_eq!(
+            key(
+                "ImOnline",
+                "AuthoredBlocks",
+                &[
+                    json!(format!("0x{}", hex::encode(1u32.encode()))),
+                    account_id(2)
+                ]
+            )
+            .unwrap(),
+            authored_blocks.0
+        );
+        // Only key1 of the DoubleMap.
+        let session_1 = key("ImOnline", "AuthoredBlocks", &[json!("1")]).unwrap();
+        assert!(authored_blocks.0.starts_with(&session_1));
+        assert_eq!(session_1.len(), 32 + 8 + 4);
+        assert_eq!(
+            key("Timestamp", "Now", &[]).unwrap(),
+            storage_prefix("Timestamp", "Now")
+        );
+        // The storage prefix of the instanced pallet differs from the module name.
+        assert_eq!(
+            key("Council", "Members", &[]).unwrap(),
+            storage_prefix("Instance1Collective", "Members")
+        );
+
+        assert!(matches!(
+            key("Timestamp", "Now", &[json!(1)]),
+            Err(StorageKeyError::TooManyKeys(_, _, 0))
+        ));
+        assert!(matches!(
+            key("System", "Unknown", &[]),
+            Err(StorageKeyError::UnknownStorage(..))
+        ));
+        assert!(matches!(
+            key("System", "Account", &[json!(true)]),
+            Err(StorageKeyError::Encode(_))
+        ));
+        // The hex of a wrong length is neither an account id nor its SCALE encoding.
+        for len in &[31, 2] {
+            assert!(matches!(
+                key(
+                    "System",
+                    "Account",
+                    &[json!(format!("0x{}", hex::encode(vec![1u8; *len])))]
+                ),
+                Err(StorageKeyError::Encode(_))
+            ));
+        }
+
+        assert_eq!(parse_key_arg("42"), json!(42));
+        assert_eq!(parse_key_arg("0x01"), json!("0x01"));
+        assert_eq!(parse_key_arg("\"5Grw\""), json!("5Grw"));
+    }
+}
@@ -0,0 +1,167 @@
+//! Iterates the entries of a storage item by the storage key prefix.
+
+use frame_metadata::StorageEntryType;
+
+use crate::decode_storage_key_value::{DecodedStorage, StorageMetadataLookupTable};
+use crate::decoder::ValueDecoder;
+use crate::metadata::StorageMetadata;
+use crate::state_source::{StateSource, StateSourceError};
+
+/// Error of iterating a storage item.
+#[derive(Debug, thiserror::Error)]
+pub enum PrefixError {
+    /// The storage item is not in the metadata.
+    #[error("Storage {0}::{1} not found")]
+    UnknownStorage(String, String),
+    /// Partial key is only supported by DoubleMap.
+    #[error("Storage {0}::{1} is not a DoubleMap")]
+    NotDoubleMap(String, String),
+    #[error(transparent)]
+    StateSource(#[from] StateSourceError),
+}
+
+/// Returns the storage prefix of `pallet::item`, i.e., `twox128(pallet) ++ twox128(item)`.
+pub fn storage_prefix(pallet: &str, item: &str) -> Vec<u8> {
+    let mut prefix = sp_core::twox_128(pallet.as_bytes()).to_vec();
+    prefix.extend_from_slice(&sp_core::twox_128(item.as_bytes()));
+    prefix
+}
+
+fn lookup<'a>(
+    table: &'a StorageMetadataLookupTable,
+    pallet: &str,
+    item: &str,
+) -> Result<&'a StorageMetadata, PrefixError> {
+    table
+        .storage_metadata(pallet, item)
+        .ok_or_else(|| PrefixError::UnknownStorage(pallet.into(), item.into()))
+}
+
+/// Returns the decoded entries of `source` whose keys start with `prefix`, e.g., the storage
+/// key with only key1 of a DoubleMap built by [`storage_key`].
+///
+/// [`storage_key`]: crate::storage_key::storage_key
+pub fn iter_key_prefix<'a, S: StateSource + ?Sized>(
+    source: &S,
+    table: &'a StorageMetadataLookupTable,
+    decoder: &'a ValueDecoder,
+    prefix: &[u8],
+) -> Result<impl Iterator<Item = DecodedStorage> + 'a, PrefixError> {
+    Ok(source
+        .prefix_pairs(prefix)?
+        .into_iter()
+        .filter_map(move |(key, value)| table.decode_storage(decoder, key, value)))
+}
+
+/// Returns the decoded entries of the storage item `pallet::item` in `source`, see
+/// [`StorageMetadataLookupTable::storage_metadata`] for `pallet`.
+pub fn iter_prefix<'a, S: StateSource + ?Sized>(
+    source: &S,
+    table: &'a StorageMetadataLookupTable,
+    decoder: &'a ValueDecoder,
+    pallet: &str,
+    item: &str,
+) -> Result<impl Iterator<Item = DecodedStorage> + 'a, PrefixError> {
+    let storage = lookup(table, pallet, item)?;
+    iter_key_prefix(source, table, decoder, &storage.prefix().0)
+}
+
+/// Returns the decoded entries of the DoubleMap `pallet::item` whose key1 is `key1`,
+/// the SCALE encoded key1.
+pub fn iter_double_map_prefix<'a, S: StateSource + ?Sized>(
+    source: &S,
+    table: &'a StorageMetadataLookupTable,
+    decoder: &'a ValueDecoder,
+    pallet: &str,
+    item: &str,
+    key1: &[u8],
+) -> Result<impl Iterator<Item = DecodedStorage> + 'a, PrefixError> {
+    let storage = lookup(table, pallet, item)?;
+    let hasher = match &storage.ty {
+        StorageEntryType::DoubleMap { hasher, .. } => hasher,
+        _ => return Err(PrefixError::NotDoubleMap(pallet.into(), item.into())),
+    };
+    let mut prefix = storage.prefix().0;
+    prefix.extend(StorageMetadata::hash(hasher, key1));
+    iter_key_prefix(source, table, decoder, &prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_storage_key_value::{DecodedKey, TransparentStorageType};
+    use crate::metadata::test_metadata;
+    use crate::state_source::StoragePair;
+    use codec::Encode;
+    use frame_metadata::StorageHasher;
+
+    fn authored_blocks_key(session_index: u32, validator: [u8; 32]) -> String {
+        let mut key = storage_prefix("ImOnline", "AuthoredBlocks");
+        key.extend(StorageMetadata::hash_key(
+            &StorageHasher::Twox64Concat,
+            &session_index,
+        ));
+        key.extend(StorageMetadata::hash_key(
+            &StorageHasher::Twox64Concat,
+            &validator,
+        ));
+        hex::encode(key)
+    }
+
+    #[test]
+    fn iter_prefix_should_work() {
+        let table: StorageMetadataLookupTable = test_metadata().into();
+        let decoder = ValueDecoder::default();
+
+        let dump: Vec<StoragePair> = vec![
+            (authored_blocks_key(1, [1; 32]), Some(3u32.encode())),
+            (authored_blocks_key(1, [2; 32]), Some(4u32.encode())),
+            (authored_blocks_key(2, [1; 32]), Some(5u32.encode())),
+            ("3a636f6465".into(), Some(b"\0asm".to_vec())),
+            (
+                hex::encode(storage_prefix("Instance1Collective", "Members")),
+                Some(vec![[1u8; 32]].encode()),
+            ),
+        ];
+
+        let all = iter_prefix(&dump, &table, &decoder, "ImOnline", "AuthoredBlocks").unwrap();
+        assert_eq!(all.count(), 3);
+
+        // The storage prefix of the instanced pallet differs from the module name.
+        for pallet in &["Council", "Instance1Collective"] {
+            let members = iter_prefix(&dump, &table, &decoder, pallet, "Members").unwrap();
+            assert_eq!(members.count(), 1);
+        }
+
+        let session_1 = iter_double_map_prefix(
+            &dump,
+            &table,
+            &decoder,
+            "ImOnline",
+            "AuthoredBlocks",
+            &1u32.encode(),
+        )
+        .unwrap()
+        .collect::<Vec<_>>();
+        assert_eq!(session_1.len(), 2);
+        match &session_1[1].key {
+            DecodedKey::Storage(key) => match &key.ty {
+                TransparentStorageType::DoubleMap { key1, key2, .. } => {
+                    assert_eq!(key1, "01000000");
+                    assert_eq!(key2, &hex::encode([2u8; 32]));
+                }
+                _ => panic!("Not DoubleMap"),
+            },
+            _ => panic!("Not storage"),
+        }
+
+        assert!(matches!(
+            iter_double_map_prefix(&dump, &table, &decoder, "System", "Account", &[]),
+            Err(PrefixError::NotDoubleMap(..))
+        ));
+        assert!(matches!(
+            iter_prefix(&dump, &table, &decoder, "System", "Unknown"),
+            Err(PrefixError::UnknownStorage(..))
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+//! Walks the state trie stored in a node database.
+
+use std::fmt;
+
+use hash_db::{HashDBRef, Prefix};
+use sp_core::{Blake2Hasher, H256};
+use sp_trie::{DBValue, Layout, TrieDB, TrieDBIterator};
+
+use crate::state_source::{ChildTriple, StateSource, StateSourceError, StoragePair};
+use crate::well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX;
+
+/// Columns of the node database, see `sc_client_db::columns` of `sc-client-db` 0.8.0-rc6.
+pub mod columns {
+    pub const META: u32 = 0;
+    pub const STATE: u32 = 1;
+    pub const KEY_LOOKUP: u32 = 3;
+    pub const HEADER: u32 = 4;
+
+    /// Number of the database columns, all of them have to be opened.
+    pub const NUM_COLUMNS: u32 = 11;
+}
+
+/// Raw key/value pair of a trie.
+pub type TriePair = (Vec<u8>, Vec<u8>);
+
+/// Database of the trie nodes.
+pub trait NodeDatabase {
+    /// Returns the trie node `hash`, `prefix` is the keyspace of the child trie if any
+    /// followed by the nibble path of the node.
+    fn get_node(&self, hash: &H256, prefix: Prefix) -> Result<Option<Vec<u8>>, StateSourceError>;
+}
+
+/// Trie nodes of the top trie or a child trie, same as `sp_trie::KeySpacedDB`.
+struct KeySpacedNodes<'a, D> {
+    db: &'a D,
+    keyspace: &'a [u8],
+}
+
+impl<'a, D: NodeDatabase> HashDBRef<Blake2Hasher, DBValue> for KeySpacedNodes<'a, D> {
+    fn get(&self, key: &H256, prefix: Prefix) -> Option<DBValue> {
+        let mut keyspaced = self.keyspace.to_vec();
+        keyspaced.extend_from_slice(prefix.0);
+        self.db
+            .get_node(key, (&keyspaced, prefix.1))
+            .unwrap_or_else(|e| {
+                eprintln!("ERROR: failed to read trie node {:?}: {}", key, e);
+                None
+            })
+    }
+
+    fn contains(&self, key: &H256, prefix: Prefix) -> bool {
+        self.get(key, prefix).is_some()
+    }
+}
+
+fn trie_error<E: fmt::Display>(e: E) -> StateSourceError {
+    StateSourceError::Trie(e.to_string())
+}
+
+/// State at the trie root `root`.
+pub struct TrieState<D> {
+    db: D,
+    root: H256,
+}
+
+impl<D: NodeDatabase> TrieState<D> {
+    pub fn new(db: D, root: H256) -> Self {
+        Self { db, root }
+    }
+
+    /// Returns the state root.
+    pub fn root(&self) -> H256 {
+        self.root
+    }
+
+    /// Returns the key/value pairs starting with `prefix` of the trie `root` in `keyspace`,
+    /// the keyspace is empty for the top trie.
+    pub fn trie_pairs(
+        &self,
+        root: &H256,
+        keyspace: &[u8],
+        prefix: &[u8],
+    ) -> Result<Vec<TriePair>, StateSourceError> {
+        let nodes = KeySpacedNodes {
+            db: &self.db,
+            keyspace,
+        };
+        let trie = TrieDB::<Layout<Blake2Hasher>>::new(&nodes, root).map_err(trie_error)?;
+        TrieDBIterator::new_prefixed(&trie, prefix)
+            .map_err(trie_error)?
+            .map(|item| item.map_err(trie_error))
+            .collect()
+    }
+}
+
+impl<D: NodeDatabase> StateSource for TrieState<D> {
+    fn top_pairs(&self) -> Result<Vec<StoragePair>, StateSourceError> {
+        self.prefix_pairs(&[])
+    }
+
+    fn prefix_pairs(&self, prefix: &[u8]) -> Result<Vec<StoragePair>, StateSourceError> {
+        Ok(self
+            .trie_pairs(&self.root, &[], prefix)?
+            .into_iter()
+            .map(|(key, value)| (hex::encode(key), Some(value)))
+            .collect())
+    }
+
+    fn child_triples(&self) -> Result<Vec<ChildTriple>, StateSourceError> {
+        let mut triples = Vec::new();
+        for (child_info, child_root) in
+            self.trie_pairs(&self.root, &[], DEFAULT_CHILD_STORAGE_KEY_PREFIX)?
+        {
+            if child_root.len() != H256::len_bytes() {
+                eprintln!(
+                    "ERROR: invalid child trie root 0x{} of 0x{}",
+                    hex::encode(&child_root),
+                    hex::encode(&child_info)
+                );
+                continue;
+            }
+            // The keyspace of a default child trie is its id.
+            let keyspace = &child_info[DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..];
+            for (key, value) in self.trie_pairs(&H256::from_slice(&child_root), keyspace, &[])? {
+                triples.push((child_info.clone(), key, value));
+            }
+        }
+        Ok(triples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hash_db::HashDB;
+    use sp_trie::{KeySpacedDBMut, MemoryDB, TrieDBMut, TrieMut};
+
+    impl NodeDatabase for MemoryDB<Blake2Hasher> {
+        fn get_node(
+            &self,
+            hash: &H256,
+            prefix: Prefix,
+        ) -> Result<Option<Vec<u8>>, StateSourceError> {
+            Ok(HashDB::get(self, hash, prefix))
+        }
+    }
+
+    #[test]
+    fn walk_trie_state_should_work() {
+        let mut db = MemoryDB::<Blake2Hasher>::default();
+
+        let child_id = b"child".to_vec();
+        let mut child_root = H256::default();
+        {
+            let mut keyspaced = KeySpacedDBMut::new(&mut db, &child_id);
+            let mut trie = TrieDBMut::<Layout<Blake2Hasher>>::new(&mut keyspaced, &mut child_root);
+            trie.insert(b"child_key", b"child_value").unwrap();
+        }
+
+        let mut child_info = DEFAULT_CHILD_STORAGE_KEY_PREFIX.to_vec();
+        child_info.extend_from_slice(&child_id);
+        let mut root = H256::default();
+        {
+            let mut trie = TrieDBMut::<Layout<Blake2Hasher>>::new(&mut db, &mut root);
+            trie.insert(b":code", b"\0asm").unwrap();
+            trie.insert(&child_info, child_root.as_bytes()).unwrap();
+        }
+
+        let state = TrieState::new(db, root);
+        let top = state.top_pairs().unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!(
+            top[0],
+            (
+                hex::encode(b":child_storage:default:child"),
+                Some(child_root.as_bytes().to_vec())
+            )
+        );
+        assert_eq!(top[1], (hex::encode(b":code"), Some(b"\0asm".to_vec())));
+
+        assert_eq!(
+            state.child_triples().unwrap(),
+            vec![(child_info, b"child_key".to_vec(), b"child_value".to_vec())]
+        );
+    }
+}
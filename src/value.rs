@@ -0,0 +1,240 @@
+use std::fmt;
+
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+
+/// Transparent representation of a decoded storage value.
+///
+/// Integers wider than 64 bits (e.g., balances) are serialized as strings and bytes
+/// are serialized as `0x` prefixed hex strings, so that the JSON output can be consumed
+/// by the tools which can not handle u128 safely.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Bytes(Vec<u8>),
+    String(String),
+    Sequence(Vec<Value>),
+    /// Struct-like value, the field order is preserved.
+    Composite(Vec<(String, Value)>),
+    /// Enum variant with the unnamed fields, `Option<T>` is also represented as a Variant.
+    Variant(String, Vec<Value>),
+}
+
+impl Value {
+    /// Constructs a `Value::Composite` from `(field_name, field_value)` pairs.
+    pub fn composite<S: Into<String>>(fields: Vec<(S, Value)>) -> Self {
+        Value::Composite(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
+    }
+
+    /// Flattens the value into `(column, cell)` pairs, the nested fields of a composite
+    /// are joined with `.`.
+    ///
+    /// Sequences and variants with fields are kept in one cell as JSON since their
+    /// length varies from row to row.
+    pub fn flatten(&self, column: &str) -> Vec<(String, String)> {
+        let mut cells = Vec::new();
+        self.flatten_into(column, &mut cells);
+        cells
+    }
+
+    fn flatten_into(&self, column: &str, cells: &mut Vec<(String, String)>) {
+        match self {
+            Value::Composite(fields) => {
+                for (name, value) in fields {
+                    value.flatten_into(&format!("{}.{}", column, name), cells);
+                }
+            }
+            Value::String(s) => cells.push((column.to_string(), s.clone())),
+            v if v.is_scalar() => cells.push((column.to_string(), v.to_string())),
+            v => {
+                let json = serde_json::to_string(v).expect("Value is always serializable; qed");
+                cells.push((column.to_string(), json));
+            }
+        }
+    }
+
+    fn is_scalar(&self) -> bool {
+        match self {
+            Value::Composite(_) | Value::Sequence(_) => false,
+            Value::Variant(_, fields) => fields.is_empty(),
+            _ => true,
+        }
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ( $( $ty:ty => $variant:ident ),* ) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(v: $ty) -> Self {
+                    Value::$variant(v)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_primitive!(
+    bool => Bool,
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    Vec<u8> => Bytes,
+    String => String
+);
+
+/// Serializes `bytes` as a `0x` prefixed hex string.
+pub fn serialize_hex<T, S>(bytes: T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]>,
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+/// Serializes `hex`, a hex string without `0x`, as a `0x` prefixed hex string.
+pub fn serialize_hex_str<T, S>(hex: T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<str>,
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("0x{}", hex.as_ref()))
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::U8(v) => serializer.serialize_u8(*v),
+            Value::U16(v) => serializer.serialize_u16(*v),
+            Value::U32(v) => serializer.serialize_u32(*v),
+            Value::U64(v) => serializer.serialize_u64(*v),
+            Value::U128(v) => serializer.serialize_str(&v.to_string()),
+            Value::Bytes(v) => serialize_hex(v, serializer),
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Sequence(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::Composite(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (name, value) in fields {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+            Value::Variant(name, fields) => match fields.as_slice() {
+                [] => serializer.serialize_str(name),
+                [field] => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry(name, field)?;
+                    map.end()
+                }
+                _ => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry(name, fields)?;
+                    map.end()
+                }
+            },
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::U8(v) => write!(f, "{}", v),
+            Value::U16(v) => write!(f, "{}", v),
+            Value::U32(v) => write!(f, "{}", v),
+            Value::U64(v) => write!(f, "{}", v),
+            Value::U128(v) => write!(f, "{}", v),
+            Value::Bytes(v) => write!(f, "0x{}", hex::encode(v)),
+            Value::String(v) => write!(f, "{:?}", v),
+            Value::Sequence(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Composite(fields) => {
+                write!(f, "{{ ")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                write!(f, " }}")
+            }
+            Value::Variant(name, fields) => {
+                write!(f, "{}", name)?;
+                if !fields.is_empty() {
+                    write!(f, "(")?;
+                    for (i, value) in fields.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", value)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flatten_value_should_work() {
+        let value = Value::composite(vec![
+            ("nonce", Value::U32(1)),
+            (
+                "data",
+                Value::composite(vec![
+                    ("free", Value::U128(100)),
+                    ("flags", Value::Variant("None".into(), Vec::new())),
+                ]),
+            ),
+            ("locks", Value::Sequence(vec![Value::U8(1), Value::U8(2)])),
+            ("who", Value::Bytes(vec![0xab])),
+        ]);
+        let cell = |column: &str, cell: &str| (column.to_string(), cell.to_string());
+        assert_eq!(
+            value.flatten("value"),
+            [
+                cell("value.nonce", "1"),
+                cell("value.data.free", "100"),
+                cell("value.data.flags", "None"),
+                cell("value.locks", "[1,2]"),
+                cell("value.who", "0xab"),
+            ]
+        );
+        assert_eq!(Value::U64(42).flatten("value"), [cell("value", "42")]);
+    }
+}
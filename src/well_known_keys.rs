@@ -0,0 +1,184 @@
+//! Well-known storage keys which are not prefixed by `twox_128(pallet) ++ twox_128(item)`.
+//!
+//! See `sp_core::storage::well_known_keys`.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::child_trie::ChildTrieOwner;
+use crate::decoder::{DecodeError, ValueDecoder};
+use crate::value::Value;
+
+/// Wasm code of the runtime.
+pub const CODE: &[u8] = b":code";
+/// Number of wasm linear memory pages required for execution of the runtime.
+pub const HEAP_PAGES: &[u8] = b":heappages";
+/// Current extrinsic index (u32) is stored under this key.
+pub const EXTRINSIC_INDEX: &[u8] = b":extrinsic_index";
+/// Changes trie configuration is stored under this key.
+pub const CHANGES_TRIE_CONFIG: &[u8] = b":changes_trie";
+/// Prefix of child storage keys.
+pub const CHILD_STORAGE_KEY_PREFIX: &[u8] = b":child_storage:";
+/// Prefix of the default child storage keys in the top trie.
+pub const DEFAULT_CHILD_STORAGE_KEY_PREFIX: &[u8] = b":child_storage:default:";
+
+/// Recognized well-known storage key.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "well_known")]
+pub enum WellKnownKey {
+    Code,
+    HeapPages,
+    ExtrinsicIndex,
+    ChangesTrie,
+    /// Root of a child trie, `child_type` is `default` for the default child trie.
+    ChildStorage {
+        child_type: String,
+        #[serde(serialize_with = "crate::value::serialize_hex")]
+        id: Vec<u8>,
+        /// Filled by `ChildTrieRegistry::annotate` if the child trie is known.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        owner: Option<ChildTrieOwner>,
+    },
+}
+
+impl WellKnownKey {
+    /// Recognizes the well-known key given the raw storage key.
+    pub fn from_key(key: &[u8]) -> Option<Self> {
+        let well_known = match key {
+            CODE => Self::Code,
+            HEAP_PAGES => Self::HeapPages,
+            EXTRINSIC_INDEX => Self::ExtrinsicIndex,
+            CHANGES_TRIE_CONFIG => Self::ChangesTrie,
+            _ if key.starts_with(DEFAULT_CHILD_STORAGE_KEY_PREFIX) => Self::ChildStorage {
+                child_type: "default".into(),
+                id: key[DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..].to_vec(),
+                owner: None,
+            },
+            _ if key.starts_with(CHILD_STORAGE_KEY_PREFIX) => {
+                let rest = &key[CHILD_STORAGE_KEY_PREFIX.len()..];
+                let (child_type, id) = match rest.iter().position(|&b| b == b':') {
+                    Some(pos) => (&rest[..pos], &rest[pos + 1..]),
+                    None => (rest, &[][..]),
+                };
+                Self::ChildStorage {
+                    child_type: String::from_utf8_lossy(child_type).into_owned(),
+                    id: id.to_vec(),
+                    owner: None,
+                }
+            }
+            _ => return None,
+        };
+        Some(well_known)
+    }
+
+    /// Recognizes the well-known key given the storage key in hex string.
+    pub fn from_hex(storage_key: &str) -> Option<Self> {
+        // All the well-known keys start with `:`.
+        if !storage_key.starts_with("3a") {
+            return None;
+        }
+        hex::decode(storage_key)
+            .ok()
+            .and_then(|key| Self::from_key(&key))
+    }
+
+    /// Returns the type of the value stored under this key.
+    pub fn value_ty(&self) -> &'static str {
+        match self {
+            Self::Code => "Vec<u8>",
+            Self::HeapPages => "u64",
+            Self::ExtrinsicIndex => "u32",
+            Self::ChangesTrie => "ChangesTrieConfiguration",
+            Self::ChildStorage { .. } => "Hash",
+        }
+    }
+
+    /// Decodes the value stored under this key.
+    ///
+    /// The runtime code is summarized as its size and hash instead of the whole blob.
+    pub fn decode_value(&self, decoder: &ValueDecoder, value: &[u8]) -> Result<Value, DecodeError> {
+        match self {
+            Self::Code => Ok(Value::composite(vec![
+                ("size", Value::U64(value.len() as u64)),
+                ("blake2_256", sp_core::blake2_256(value).to_vec().into()),
+            ])),
+            _ => decoder.decode_all(self.value_ty(), value),
+        }
+    }
+}
+
+impl fmt::Display for WellKnownKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Code => write!(f, ":code"),
+            Self::HeapPages => write!(f, ":heappages"),
+            Self::ExtrinsicIndex => write!(f, ":extrinsic_index"),
+            Self::ChangesTrie => write!(f, ":changes_trie"),
+            Self::ChildStorage {
+                child_type,
+                id,
+                owner,
+            } => {
+                write!(f, ":child_storage:{}:0x{}", child_type, hex::encode(id))?;
+                match owner {
+                    Some(owner) => write!(f, " ({} {})", owner.pallet, owner.name),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::Encode;
+
+    #[test]
+    fn recognize_well_known_keys_should_work() {
+        assert_eq!(
+            WellKnownKey::from_hex("3a636f6465"),
+            Some(WellKnownKey::Code)
+        );
+        assert_eq!(
+            WellKnownKey::from_hex(&hex::encode(HEAP_PAGES)),
+            Some(WellKnownKey::HeapPages)
+        );
+        let mut child_key = DEFAULT_CHILD_STORAGE_KEY_PREFIX.to_vec();
+        child_key.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(
+            WellKnownKey::from_key(&child_key),
+            Some(WellKnownKey::ChildStorage {
+                child_type: "default".into(),
+                id: vec![1, 2, 3],
+                owner: None,
+            })
+        );
+        assert_eq!(
+            WellKnownKey::from_hex(
+                "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn decode_well_known_values_should_work() {
+        let decoder = ValueDecoder::default();
+        assert_eq!(
+            WellKnownKey::HeapPages
+                .decode_value(&decoder, &1024u64.encode())
+                .unwrap(),
+            Value::U64(1024)
+        );
+        let code = WellKnownKey::Code.decode_value(&decoder, b"\0asm").unwrap();
+        assert_eq!(
+            code,
+            Value::composite(vec![
+                ("size", Value::U64(4)),
+                ("blake2_256", sp_core::blake2_256(b"\0asm").to_vec().into()),
+            ])
+        );
+    }
+}